@@ -0,0 +1,45 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Index};
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate::crate_path(&input, "bundle")?;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`Bundle` can only be derived for structs",
+        ));
+    };
+
+    let mut generics = input.generics.clone();
+    let mut adds = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = Index::from(index);
+                quote!(#index)
+            }
+        };
+        adds.push(quote!(#krate::bundle::Bundle::add_to(self.#member, entity);));
+
+        let ty = &field.ty;
+        if !input.generics.params.is_empty() {
+            generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#ty: #krate::bundle::Bundle));
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #krate::bundle::Bundle for #name #ty_generics #where_clause {
+            fn add_to(self, entity: &mut #krate::entity_builder::EntityBuilder<'_>) {
+                #(#adds)*
+            }
+        }
+    })
+}
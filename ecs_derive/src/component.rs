@@ -0,0 +1,169 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parenthesized, parse_quote, punctuated::Punctuated, DeriveInput, Ident, LitStr, Path, Token,
+    Type, WherePredicate,
+};
+
+/// The options given with `#[component(...)]`.
+#[derive(Default)]
+struct Options {
+    krate: Option<Path>,
+    storage: Option<Ident>,
+    cloneable: bool,
+    requires: Vec<Type>,
+    on_add: Option<Path>,
+    on_insert: Option<Path>,
+    on_remove: Option<Path>,
+    map_entities: bool,
+    name: Option<LitStr>,
+    reflect: Vec<Ident>,
+    serializable: Option<LitStr>,
+}
+
+impl Options {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut options = Self::default();
+
+        for attr in input.attrs.iter() {
+            if !attr.path().is_ident("component") {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    options.krate = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("storage") {
+                    let kind: LitStr = meta.value()?.parse()?;
+                    let variant = match kind.value().as_str() {
+                        "dense" => "Dense",
+                        "sparse" => "Sparse",
+                        _ => {
+                            return Err(syn::Error::new(
+                                kind.span(),
+                                "expected \"dense\" or \"sparse\"",
+                            ))
+                        }
+                    };
+                    options.storage = Some(Ident::new(variant, kind.span()));
+                } else if meta.path.is_ident("cloneable") {
+                    options.cloneable = true;
+                } else if meta.path.is_ident("requires") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let types = Punctuated::<Type, Token![,]>::parse_terminated(&content)?;
+                    options.requires.extend(types);
+                } else if meta.path.is_ident("on_add") {
+                    options.on_add = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("on_insert") {
+                    options.on_insert = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("on_remove") {
+                    options.on_remove = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("map_entities") {
+                    options.map_entities = true;
+                } else if meta.path.is_ident("name") {
+                    options.name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("reflect") {
+                    meta.parse_nested_meta(|meta| match meta.path.get_ident() {
+                        Some(ident) if ident == "Default" || ident == "Debug" => {
+                            options.reflect.push(ident.clone());
+                            Ok(())
+                        }
+                        _ => Err(meta.error("expected `Default` or `Debug`")),
+                    })?;
+                } else if meta.path.is_ident("serializable") {
+                    options.serializable = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unknown component option"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(options)
+    }
+
+    /// The calls configuring a `ComponentRegistration`, along with the bounds they need from the component type.
+    fn calls(&self, krate: &Path) -> (Vec<TokenStream>, Vec<WherePredicate>) {
+        let mut calls = Vec::new();
+        let mut bounds: Vec<WherePredicate> = Vec::new();
+
+        if let Some(kind) = &self.storage {
+            calls.push(quote!(storage(#krate::component::StorageKind::#kind)));
+        }
+        if self.cloneable {
+            calls.push(quote!(cloneable()));
+            bounds.push(parse_quote!(Self: ::core::clone::Clone));
+        }
+        for required in self.requires.iter() {
+            calls.push(quote!(requires::<#required>()));
+            bounds.push(parse_quote!(#required: #krate::component::Component + ::core::default::Default + 'static));
+        }
+        for (hook, path) in [
+            ("on_add", &self.on_add),
+            ("on_insert", &self.on_insert),
+            ("on_remove", &self.on_remove),
+        ] {
+            if let Some(path) = path {
+                let hook = Ident::new(hook, proc_macro2::Span::call_site());
+                calls.push(quote!(#hook(#path)));
+            }
+        }
+        if self.map_entities {
+            calls.push(quote!(map_entities()));
+            bounds.push(parse_quote!(Self: #krate::entity_map::MapEntities));
+        }
+        if let Some(name) = &self.name {
+            calls.push(quote!(named(#name)));
+        }
+        for reflect in self.reflect.iter() {
+            if reflect == "Default" {
+                calls.push(quote!(reflect_default()));
+                bounds.push(parse_quote!(Self: ::core::default::Default));
+            } else {
+                calls.push(quote!(reflect_debug()));
+                bounds.push(parse_quote!(Self: ::core::fmt::Debug));
+            }
+        }
+        if let Some(name) = &self.serializable {
+            calls.push(quote!(serializable(#name)));
+            bounds.push(parse_quote!(
+                Self: ::serde::Serialize + ::serde::de::DeserializeOwned
+            ));
+        }
+
+        (calls, bounds)
+    }
+}
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let options = Options::parse(&input)?;
+    let krate = options.krate.clone().unwrap_or_else(|| parse_quote!(::ecs));
+    let (calls, bounds) = options.calls(&krate);
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    // non-generic types get their errors from the calls instead
+    if !generics.params.is_empty() {
+        generics.make_where_clause().predicates.extend(bounds);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let configure = (!calls.is_empty()).then(|| {
+        quote! {
+            fn configure(registration: &mut #krate::hooks::ComponentRegistration<'_, Self>)
+            where
+                Self: Sized + 'static,
+            {
+                registration #(.#calls)*;
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #krate::component::Component for #name #ty_generics #where_clause {
+            #configure
+        }
+    })
+}
@@ -0,0 +1,79 @@
+//! Derive macros for the `ecs` crate. Use them through `ecs::prelude` rather than depending on this crate directly.
+mod bundle;
+mod component;
+mod system_param;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, parse_quote, DeriveInput, Path};
+
+/// Implements `Component` for a struct or enum, generic or not.
+///
+/// The `#[component(...)]` attribute configures the type the first time it is registered with a `World`, whether
+/// explicitly or by adding a component:
+/// - `storage = "dense"` or `storage = "sparse"`: like `ComponentRegistration::storage`
+/// - `cloneable`: like `World::register_cloneable`
+/// - `requires(A, B)`: like `ComponentRegistration::requires`, for each type
+/// - `on_add = path`, `on_insert = path`, `on_remove = path`: lifecycle hooks
+/// - `map_entities`: like `ComponentRegistration::map_entities`
+/// - `name = "Name"`: like `ComponentRegistration::named`
+/// - `reflect(Default, Debug)`: like `ComponentRegistration::reflect_default` and `ComponentRegistration::reflect_debug`
+/// - `serializable = "Name"`: like `ComponentRegistration::serializable`, needs the `serde` feature of `ecs`
+/// - `crate = path`: the path of the `ecs` crate, `::ecs` by default
+///
+/// Generic types only implement `Component` when they meet the bounds the options need, e.g. `Clone` for `cloneable`.
+#[proc_macro_derive(Component, attributes(component))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    component::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Bundle` for a struct whose fields are all bundles, e.g. components or other bundle structs.
+///
+/// `#[bundle(crate = path)]` sets the path of the `ecs` crate, `::ecs` by default.
+#[proc_macro_derive(Bundle, attributes(bundle))]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    bundle::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `SystemParam` for a struct whose fields are all system parameters, e.g. resources and queries.
+/// The struct can have at most one lifetime parameter, the borrow of the world, and no type or const parameters.
+///
+/// `#[system_param(crate = path)]` sets the path of the `ecs` crate, `::ecs` by default.
+#[proc_macro_derive(SystemParam, attributes(system_param))]
+pub fn derive_system_param(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    system_param::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Reads the path of the `ecs` crate from `#[attribute(crate = path)]`, the only option of the derives other than `Component`.
+fn crate_path(input: &DeriveInput, attribute: &str) -> syn::Result<Path> {
+    let mut krate = parse_quote!(::ecs);
+
+    for attr in input.attrs.iter() {
+        if !attr.path().is_ident(attribute) {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.parse()?;
+
+                Ok(())
+            } else {
+                Err(meta.error(format!("unknown {attribute} option")))
+            }
+        })?;
+    }
+
+    Ok(krate)
+}
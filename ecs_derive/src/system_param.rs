@@ -0,0 +1,69 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, GenericParam, Index};
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate::crate_path(&input, "system_param")?;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`SystemParam` can only be derived for structs",
+        ));
+    };
+
+    let mut lifetime = None;
+    for param in input.generics.params.iter() {
+        match param {
+            GenericParam::Lifetime(param) if lifetime.is_none() => lifetime = Some(&param.lifetime),
+            GenericParam::Lifetime(param) => {
+                return Err(syn::Error::new_spanned(
+                    param,
+                    "`SystemParam` structs can only have one lifetime, the borrow of the world",
+                ))
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    param,
+                    "`SystemParam` structs cannot have type or const parameters",
+                ))
+            }
+        }
+    }
+
+    let fetches = data.fields.iter().enumerate().map(|(index, field)| {
+        let ty = &field.ty;
+        let fetch = quote!(<#ty as #krate::system_param::SystemParam>::fetch(world));
+
+        match &field.ident {
+            Some(ident) => quote!(#ident: #fetch),
+            None => {
+                let index = Index::from(index);
+                quote!(#index: #fetch)
+            }
+        }
+    });
+    let body = match data.fields {
+        Fields::Unit => quote!(),
+        _ => quote!({ #(#fetches,)* }),
+    };
+
+    let name = &input.ident;
+    let (impl_generics, self_ty, item_ty) = match lifetime {
+        Some(lifetime) => (
+            quote!(<#lifetime>),
+            quote!(#name<#lifetime>),
+            quote!(#name<'__world>),
+        ),
+        None => (quote!(), quote!(#name), quote!(#name)),
+    };
+
+    Ok(quote! {
+        impl #impl_generics #krate::system_param::SystemParam for #self_ty {
+            type Item<'__world> = #item_ty;
+
+            fn fetch(world: &#krate::world::World) -> Self::Item<'_> {
+                #name #body
+            }
+        }
+    })
+}
@@ -1,14 +1,68 @@
 #![allow(unused)]
 use crate::{
-    scheduler::{Scheduler, WorldFn},
+    event::Events,
+    plugin::{Plugin, PluginGroup},
+    runner::{run_loop, Runner},
+    scheduler::{ParamFn, Scheduler, WorldFn},
+    sub_app::SubApp,
+    system_param::SystemParam,
     world::World,
 };
+use std::{
+    any::{Any, TypeId},
+    collections::HashSet,
+    process::{ExitCode, Termination},
+};
+
+/// How an app exited, returned by `App::run`. Request an exit with `World::exit`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AppExit {
+    #[default]
+    Success,
+    /// Exited with a non-zero error code.
+    Error(u8),
+}
+
+impl AppExit {
+    pub fn is_success(&self) -> bool {
+        matches!(self, AppExit::Success)
+    }
+
+    /// The process exit code, `0` for success.
+    pub fn code(&self) -> u8 {
+        match self {
+            AppExit::Success => 0,
+            AppExit::Error(code) => *code,
+        }
+    }
+}
+
+/// Lets `main` return the `AppExit` from `App::run` as the process exit code.
+impl Termination for AppExit {
+    fn report(self) -> ExitCode {
+        ExitCode::from(self.code())
+    }
+}
 
 /// Core application struct with the world and scheduler.
 #[derive(Default)]
 pub struct App {
     world: World,
     scheduler: Scheduler,
+    // swap the event buffers at the end of every frame
+    event_updates: Vec<WorldFn>,
+    // types of the added events, whether or not their resource was added to the world first
+    events: HashSet<TypeId>,
+    // types of the added plugins
+    plugins: HashSet<TypeId>,
+    // types of the plugins added only as dependencies of other plugins, which can still be added explicitly once
+    dependencies: HashSet<TypeId>,
+    // whether the startup systems have run
+    started: bool,
+    // `None` runs `run_loop`
+    runner: Option<Runner>,
+    // updated after the main world every frame, in order
+    pub(crate) sub_apps: Vec<(&'static str, SubApp)>,
 }
 
 impl App {
@@ -17,6 +71,13 @@ impl App {
         Self {
             world: World::new(),
             scheduler: Scheduler::new(),
+            event_updates: Vec::new(),
+            events: HashSet::new(),
+            plugins: HashSet::new(),
+            dependencies: HashSet::new(),
+            started: false,
+            runner: None,
+            sub_apps: Vec::new(),
         }
     }
 
@@ -34,6 +95,100 @@ impl App {
         self
     }
 
+    /// Adds a system taking the parameter `P` instead of the world, e.g. a struct of queries and resources deriving
+    /// `SystemParam`. `P` has to be named, as in `app.add_system_with::<Movement>(movement)`.
+    /// Order of insertion does matter; systems that are added first are run first.
+    pub fn add_system_with<P>(&mut self, system: ParamFn<P>) -> &mut Self
+    where
+        P: SystemParam + 'static,
+    {
+        self.scheduler.add_system_with::<P>(system);
+
+        self
+    }
+
+    /// Adds a shutdown system to the app, run at the end of every frame in which an exit was requested,
+    /// e.g. to flush saves or to veto the exit with `World::cancel_exit`.
+    /// Order of insertion does matter; systems that are added first are run first.
+    pub fn add_shutdown_system(&mut self, shutdown_system: WorldFn) -> &mut Self {
+        self.scheduler.add_shutdown_system(shutdown_system);
+
+        self
+    }
+
+    /// Adds the event type `E`, setting up an `Events<E>` resource whose buffers are swapped at the end of every frame.
+    /// Adding the same event twice does nothing.
+    pub fn add_event<E>(&mut self) -> &mut Self
+    where
+        E: 'static,
+    {
+        if self.events.insert(TypeId::of::<E>()) {
+            self.world.add_event::<E>();
+            self.event_updates.push(Events::<E>::update_system);
+        }
+
+        self
+    }
+
+    /// Adds a plugin, first adding any of its dependencies the app does not have yet.
+    /// Adding a unique plugin that was only added as a dependency so far does nothing.
+    /// # Panics
+    /// Panics if the plugin is unique and was already added.
+    pub fn add_plugin<P>(&mut self, plugin: P) -> &mut Self
+    where
+        P: Plugin,
+    {
+        self.add_boxed_plugin(Box::new(plugin));
+
+        self
+    }
+
+    /// Adds several plugins in order, e.g. `app.add_plugins((InputPlugin, PhysicsPlugin))`.
+    /// # Panics
+    /// Panics if one of the plugins is unique and was already added.
+    pub fn add_plugins<G>(&mut self, group: G) -> &mut Self
+    where
+        G: PluginGroup,
+    {
+        for plugin in group.plugins() {
+            self.add_boxed_plugin(plugin);
+        }
+
+        self
+    }
+
+    fn add_boxed_plugin(&mut self, plugin: Box<dyn Plugin>) {
+        let id = (*plugin).type_id();
+        // already added as a dependency of a plugin that came first, e.g. `add_plugins((B, A))` where `B` depends on `A`
+        if plugin.is_unique() && self.dependencies.remove(&id) {
+            return;
+        }
+        assert!(
+            !plugin.is_unique() || !self.plugins.contains(&id),
+            "Plugin {} was already added",
+            plugin.name()
+        );
+        // marked as added before its dependencies, so dependency cycles end
+        self.plugins.insert(id);
+
+        for dependency in plugin.dependencies() {
+            let dependency_id = (*dependency).type_id();
+            if !self.plugins.contains(&dependency_id) {
+                self.add_boxed_plugin(dependency);
+                self.dependencies.insert(dependency_id);
+            }
+        }
+        plugin.build(self);
+    }
+
+    /// Checks if the plugin `P` was added.
+    pub fn has_plugin<P>(&self) -> bool
+    where
+        P: Plugin,
+    {
+        self.plugins.contains(&TypeId::of::<P>())
+    }
+
     fn run_commands(&mut self) {
         let mut command_queue = {
             let mut command_queue = self.world.get_commands();
@@ -42,13 +197,108 @@ impl App {
         command_queue.run_commands(&mut self.world);
     }
 
-    /// Driver of `App`
-    pub fn run(&mut self) {
+    fn update_events(&mut self) {
+        for update in self.event_updates.iter() {
+            update(&mut self.world);
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Runs the startup systems, followed by their commands. Does nothing if they already ran.
+    /// If a startup system requested an exit, the shutdown systems run right away, and can still veto it.
+    pub fn startup(&mut self) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+
         self.scheduler.run_startup_systems(&mut self.world);
+        self.run_commands();
+
+        if !self.world.active() {
+            self.run_shutdown();
+        }
+    }
+
+    /// Runs a single frame: the systems, then the commands they queued, then the event buffer swap, then the sub-apps.
+    /// The startup systems run first if they have not run yet, and the shutdown systems run last if an exit was requested.
+    /// Nothing else runs if the startup systems requested an exit that was not vetoed.
+    pub fn update(&mut self) {
+        if !self.started {
+            self.startup();
+            if !self.world.active() {
+                return;
+            }
+        }
+
+        self.run_frame();
 
-        while self.world.active() {
-            self.scheduler.run_systems(&mut self.world);
+        if !self.world.active() {
+            self.run_shutdown();
+        }
+    }
+
+    /// Runs a frame of a sub-app, like `App::update`, but clears the exits it requested instead of running its shutdown
+    /// systems. Those run once the main app shuts down, from `App::run_shutdown`.
+    pub(crate) fn update_sub_app(&mut self) {
+        if !self.started {
+            self.started = true;
+            self.scheduler.run_startup_systems(&mut self.world);
             self.run_commands();
         }
+
+        self.run_frame();
+        self.world.cancel_exit();
+    }
+
+    // the systems, their commands, the event buffer swap and the sub-apps, without the startup and shutdown systems
+    fn run_frame(&mut self) {
+        self.scheduler.run_systems(&mut self.world);
+        self.run_commands();
+        self.update_events();
+        self.world.update_removed();
+        self.update_sub_apps();
+    }
+
+    /// Runs the shutdown systems, followed by their commands. Called by `App::update` when an exit was requested;
+    /// runners that request an exit themselves, e.g. when a window is closed, should call it too.
+    /// The app is active again afterwards if a shutdown system called `World::cancel_exit`; otherwise the shutdown systems
+    /// of the sub-apps run next, once per sub-app.
+    pub fn run_shutdown(&mut self) {
+        self.scheduler.run_shutdown_systems(&mut self.world);
+        self.run_commands();
+
+        if let Some(exit) = self.world.exit_requested() {
+            self.shutdown_sub_apps(exit);
+        }
+    }
+
+    /// Sets the function `App::run` hands the app to, e.g. `runner::run_fixed_rate` for a server
+    /// or a windowing backend that owns the event loop. Replaces the previous runner.
+    pub fn set_runner<R>(&mut self, runner: R) -> &mut Self
+    where
+        R: FnOnce(&mut App) + 'static,
+    {
+        self.runner = Some(Box::new(runner));
+
+        self
+    }
+
+    /// Driver of `App`. Hands the app to its runner, which by default runs frames until an exit is requested.
+    /// Returns the requested exit, or `AppExit::Success` if the runner stopped without one.
+    pub fn run(&mut self) -> AppExit {
+        match self.runner.take() {
+            Some(runner) => runner(self),
+            None => run_loop(self),
+        }
+
+        self.world.exit_requested().unwrap_or_default()
     }
 }
@@ -0,0 +1,45 @@
+use crate::{component::Component, entity_builder::EntityBuilder};
+
+/// A group of components added to an entity together with `EntityBuilder::with_bundle`.
+/// Implemented for components, for tuples of bundles, and for structs of bundles with `#[derive(Bundle)]`.
+pub trait Bundle {
+    /// Adds every component of the bundle to the entity being built.
+    fn add_to(self, entity: &mut EntityBuilder<'_>);
+}
+
+impl<C> Bundle for C
+where
+    C: Component + 'static,
+{
+    fn add_to(self, entity: &mut EntityBuilder<'_>) {
+        entity.with(self);
+    }
+}
+
+macro_rules! __impl_bundle {
+    ($($t:ident),*) => {
+        impl<$($t),*> Bundle for ($($t,)*)
+        where
+            $($t: Bundle,)*
+        {
+            #[allow(non_snake_case)]
+            fn add_to(self, entity: &mut EntityBuilder<'_>) {
+                let ($($t,)*) = self;
+                $($t.add_to(entity);)*
+            }
+        }
+    };
+}
+
+__impl_bundle!(T1);
+__impl_bundle!(T1, T2);
+__impl_bundle!(T1, T2, T3);
+__impl_bundle!(T1, T2, T3, T4);
+__impl_bundle!(T1, T2, T3, T4, T5);
+__impl_bundle!(T1, T2, T3, T4, T5, T6);
+__impl_bundle!(T1, T2, T3, T4, T5, T6, T7);
+__impl_bundle!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+/// Implements `Bundle` for a struct whose fields are all bundles, adding them in order. Use `#[bundle(crate = path)]`
+/// if the `ecs` crate is not available as `::ecs`.
+pub use ecs_derive::Bundle;
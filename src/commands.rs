@@ -1,4 +1,9 @@
-use crate::{component::Component, world::World};
+use crate::{
+    component::{Component, EntityId},
+    entity_builder::EntityBuilder,
+    prefab::Prefab,
+    world::World,
+};
 
 type Command = Box<dyn FnMut(&mut World)>;
 
@@ -13,6 +18,78 @@ impl Commands {
         self.commands.push(Box::new(command));
     }
 
+    /// Queues the creation of an entity from a prefab.
+    pub fn instantiate(&mut self, prefab: &Prefab) {
+        let prefab = prefab.clone();
+
+        self.add_command(move |world| {
+            world.instantiate(&prefab);
+        });
+    }
+
+    /// Queues the creation of an entity from a prefab, keeping a link to the prefab for later propagation.
+    pub fn instantiate_linked(&mut self, prefab: &Prefab) {
+        let prefab = prefab.clone();
+
+        self.add_command(move |world| {
+            world.instantiate_linked(&prefab);
+        });
+    }
+
+    /// Queues the creation of an entity from a prefab, then calls `build` with its `EntityBuilder`, like `World::instantiate`.
+    /// Components added through the builder replace the prefab's values for this instance.
+    pub fn instantiate_with(&mut self, prefab: &Prefab, build: impl FnOnce(&mut EntityBuilder) + 'static) {
+        let prefab = prefab.clone();
+        let mut build = Some(build);
+
+        self.add_command(move |world| {
+            if let Some(build) = build.take() {
+                build(&mut world.instantiate(&prefab));
+            }
+        });
+    }
+
+    /// Like `Commands::instantiate_with`, but keeps a link to the prefab, like `World::instantiate_linked`.
+    /// Components added through the builder are recorded as overrides.
+    pub fn instantiate_linked_with(&mut self, prefab: &Prefab, build: impl FnOnce(&mut EntityBuilder) + 'static) {
+        let prefab = prefab.clone();
+        let mut build = Some(build);
+
+        self.add_command(move |world| {
+            if let Some(build) = build.take() {
+                build(&mut world.instantiate_linked(&prefab));
+            }
+        });
+    }
+
+    /// Queues a trigger without a target, see `World::trigger`.
+    pub fn trigger<E>(&mut self, event: E)
+    where
+        E: 'static,
+    {
+        let mut event = Some(event);
+
+        self.add_command(move |world| {
+            if let Some(event) = event.take() {
+                world.trigger(event);
+            }
+        });
+    }
+
+    /// Queues a trigger targeting an entity, see `World::trigger_targets`.
+    pub fn trigger_targets<E>(&mut self, event: E, key: EntityId)
+    where
+        E: 'static,
+    {
+        let mut event = Some(event);
+
+        self.add_command(move |world| {
+            if let Some(event) = event.take() {
+                world.trigger_targets(event, key);
+            }
+        });
+    }
+
     pub fn run_commands(&mut self, world: &mut World) {
         // no need to consume since we use std::take
         for command in self.commands.iter_mut() {
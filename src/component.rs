@@ -1,25 +1,44 @@
 #![allow(unused)]
-use slotmap::{new_key_type, SecondaryMap};
+use crate::{
+    hooks::{ComponentRegistration, HookFn},
+    observer::{trigger_on_add, trigger_on_remove},
+};
+use slotmap::{new_key_type, Key, KeyData, SecondaryMap, SparseSecondaryMap};
 use std::{
     any::Any,
     cell::{Ref, RefCell, RefMut},
 };
 
 /// A marker trait for components. This trait **must** be implemented for components one wants to add to the `World`.
-pub trait Component {}
+/// Usually implemented with `#[derive(Component)]`.
+pub trait Component {
+    /// Configures the component type the first time it is registered with a `World`, e.g. to set hooks or required components.
+    /// Implemented by `#[derive(Component)]` from its `#[component(...)]` attribute.
+    fn configure(registration: &mut ComponentRegistration<'_, Self>)
+    where
+        Self: Sized + 'static,
+    {
+    }
+}
+
+/// Implements `Component`, see the `ecs_derive` crate for the `#[component(...)]` options.
+pub use ecs_derive::Component;
 
 /// Macro used to automatically generate component structs that implement the `Component` trait.
+/// A thin wrapper around `#[derive(Component)]`, which also accepts `#[component(...)]` attributes.
 #[macro_export]
 macro_rules! make_component {
-    ($(#[$outer:meta])* $vis:vis struct $name:ident $($rest:tt)*) => {
+    ($(#[$outer:meta])* $vis:vis struct $($rest:tt)*) => {
+        #[derive($crate::component::Component)]
         $(#[$outer])*
-        $vis struct $name $($rest)*
-        impl $crate::component::Component for $name {}
+        #[component(crate = $crate)]
+        $vis struct $($rest)*
     };
-    ($(#[$outer:meta])* $vis:vis enum $name:ident $($rest:tt)*) => {
+    ($(#[$outer:meta])* $vis:vis enum $($rest:tt)*) => {
+        #[derive($crate::component::Component)]
         $(#[$outer])*
-        $vis enum $name $($rest)*
-        impl $crate::component::Component for $name {}
+        #[component(crate = $crate)]
+        $vis enum $($rest)*
     };
 }
 
@@ -29,13 +48,168 @@ new_key_type! {
 
 impl Component for EntityId {}
 
+impl EntityId {
+    /// Packs the ID into a `u64`, e.g. to write it into a file or send it over the network.
+    pub fn to_bits(self) -> u64 {
+        self.data().as_ffi()
+    }
+
+    /// Unpacks an ID created by `EntityId::to_bits`. Other bits may give an ID that matches no entity, or one that aliases a live entity,
+    /// so only pass bits that came from `to_bits`.
+    pub fn from_bits(bits: u64) -> Self {
+        KeyData::from_ffi(bits).into()
+    }
+}
+
+/// A type-erased view of a `ComponentStorage<T>`, letting `World` work with every storage without knowing `T`.
+pub trait Storage: Any {
+    /// Removes the component for the given entity ID, dropping it.
+    fn remove_entry(&mut self, key: EntityId);
+
+    /// Checks if a component exists for the given entity ID.
+    fn contains_entry(&self, key: EntityId) -> bool;
+
+    /// Checks if the storage was created with `ComponentStorage::cloneable`.
+    fn is_cloneable(&self) -> bool;
+
+    /// Copies the component of `from` onto `to`. Does nothing if the storage is not cloneable or `from` has no component.
+    fn clone_entry(&mut self, from: EntityId, to: EntityId);
+
+    /// Copies every component, if the storage is cloneable. Used by `World::snapshot`.
+    fn snapshot(&self) -> Option<Box<dyn Any>>;
+
+    /// Replaces every component with a copy of the ones from `Storage::snapshot`, or removes them all if there is no snapshot.
+    fn restore(&mut self, snapshot: Option<&dyn Any>);
+
+    /// Removes the components of every entity for which `keep` returns `false`.
+    fn retain_entries(&mut self, keep: &dyn Fn(EntityId) -> bool);
+
+    /// Removes the component for the given entity ID, returning it boxed. Used to move components between worlds.
+    fn take_entry(&mut self, key: EntityId) -> Option<Box<dyn Any>>;
+
+    /// Inserts a component taken with `Storage::take_entry`.
+    /// # Panics
+    /// Panics if the component is not of the stored type.
+    fn insert_entry(&mut self, key: EntityId, entry: Box<dyn Any>);
+
+    /// Creates an empty storage of the same component type and `StorageKind`, cloneable if this one is.
+    fn empty(&self) -> Box<dyn Storage>;
+
+    /// The name of the stored component type, for diagnostics.
+    fn type_name(&self) -> &'static str;
+
+    /// A function firing `OnAdd<T>` observers for the stored component type.
+    fn add_trigger(&self) -> HookFn;
+
+    /// A function firing `OnRemove<T>` observers for the stored component type.
+    fn remove_trigger(&self) -> HookFn;
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// How a `ComponentStorage` lays out its components. Set with `ComponentRegistration::storage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageKind {
+    /// A slot for every entity, indexed by its ID. Fastest to look up; the default.
+    #[default]
+    Dense,
+    /// A hash map holding only the entities that have the component. Uses less memory for components few entities have.
+    Sparse,
+}
+
+/// The components of a `ComponentStorage`, laid out according to its `StorageKind`.
+enum Entries<T> {
+    Dense(SecondaryMap<EntityId, RefCell<T>>),
+    Sparse(SparseSecondaryMap<EntityId, RefCell<T>>),
+}
+
+impl<T> Default for Entries<T> {
+    fn default() -> Self {
+        Self::Dense(SecondaryMap::new())
+    }
+}
+
+impl<T> Entries<T> {
+    fn new(kind: StorageKind) -> Self {
+        match kind {
+            StorageKind::Dense => Self::Dense(SecondaryMap::new()),
+            StorageKind::Sparse => Self::Sparse(SparseSecondaryMap::new()),
+        }
+    }
+
+    fn kind(&self) -> StorageKind {
+        match self {
+            Self::Dense(_) => StorageKind::Dense,
+            Self::Sparse(_) => StorageKind::Sparse,
+        }
+    }
+
+    fn insert(&mut self, key: EntityId, entry: RefCell<T>) -> Option<RefCell<T>> {
+        match self {
+            Self::Dense(entries) => entries.insert(key, entry),
+            Self::Sparse(entries) => entries.insert(key, entry),
+        }
+    }
+
+    fn remove(&mut self, key: EntityId) -> Option<RefCell<T>> {
+        match self {
+            Self::Dense(entries) => entries.remove(key),
+            Self::Sparse(entries) => entries.remove(key),
+        }
+    }
+
+    fn contains_key(&self, key: EntityId) -> bool {
+        match self {
+            Self::Dense(entries) => entries.contains_key(key),
+            Self::Sparse(entries) => entries.contains_key(key),
+        }
+    }
+
+    fn get(&self, key: EntityId) -> Option<&RefCell<T>> {
+        match self {
+            Self::Dense(entries) => entries.get(key),
+            Self::Sparse(entries) => entries.get(key),
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = (EntityId, &RefCell<T>)> + '_> {
+        match self {
+            Self::Dense(entries) => Box::new(entries.iter()),
+            Self::Sparse(entries) => Box::new(entries.iter()),
+        }
+    }
+
+    fn drain(&mut self) -> Vec<(EntityId, RefCell<T>)> {
+        match self {
+            Self::Dense(entries) => entries.drain().collect(),
+            Self::Sparse(entries) => entries.drain().collect(),
+        }
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(EntityId) -> bool) {
+        match self {
+            Self::Dense(entries) => entries.retain(|key, _| keep(key)),
+            Self::Sparse(entries) => entries.retain(|key, _| keep(key)),
+        }
+    }
+
+    fn clear(&mut self) {
+        match self {
+            Self::Dense(entries) => entries.clear(),
+            Self::Sparse(entries) => entries.clear(),
+        }
+    }
+}
+
 /// A component storage system for holding components of a single type for all entities.
 #[derive(Default)]
 pub struct ComponentStorage<T>
 where
     T: Component + 'static,
 {
-    components: SecondaryMap<EntityId, RefCell<T>>,
+    components: Entries<T>,
+    cloner: Option<fn(&T) -> T>,
 }
 
 impl<T> ComponentStorage<T>
@@ -45,10 +219,29 @@ where
     /// Creates a new `ComponentStorage` instance.
     pub fn new() -> Self {
         Self {
-            components: SecondaryMap::new(),
+            components: Entries::default(),
+            cloner: None,
         }
     }
 
+    /// How the components are laid out.
+    pub fn kind(&self) -> StorageKind {
+        self.components.kind()
+    }
+
+    /// Changes how the components are laid out, keeping the current ones.
+    pub fn set_kind(&mut self, kind: StorageKind) {
+        if self.kind() == kind {
+            return;
+        }
+
+        let mut components = Entries::new(kind);
+        for (key, entry) in self.components.drain() {
+            components.insert(key, entry);
+        }
+        self.components = components;
+    }
+
     /// Inserts a new component for the given entity ID.
     pub fn insert(&mut self, key: EntityId, entry: T) -> Option<T> {
         self.components
@@ -76,3 +269,113 @@ where
         self.components.get(key).map(|inner| inner.borrow_mut())
     }
 }
+
+impl<T> ComponentStorage<T>
+where
+    T: Component + Clone + 'static,
+{
+    /// Creates a new `ComponentStorage` instance whose components can be copied by `World::clone_entity`.
+    pub fn cloneable() -> Self {
+        Self {
+            components: Entries::default(),
+            cloner: Some(T::clone),
+        }
+    }
+
+    /// Makes the stored components copyable by `World::clone_entity`, keeping the current ones.
+    pub fn make_cloneable(&mut self) {
+        self.cloner = Some(T::clone);
+    }
+}
+
+impl<T> Storage for ComponentStorage<T>
+where
+    T: Component + 'static,
+{
+    fn remove_entry(&mut self, key: EntityId) {
+        self.components.remove(key);
+    }
+
+    fn contains_entry(&self, key: EntityId) -> bool {
+        self.contains(key)
+    }
+
+    fn is_cloneable(&self) -> bool {
+        self.cloner.is_some()
+    }
+
+    fn clone_entry(&mut self, from: EntityId, to: EntityId) {
+        let Some(cloner) = self.cloner else {
+            return;
+        };
+        let Some(entry) = self.get(from).map(|entry| cloner(&entry)) else {
+            return;
+        };
+
+        self.insert(to, entry);
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Any>> {
+        let cloner = self.cloner?;
+        let entries = self
+            .components
+            .iter()
+            .map(|(key, entry)| (key, cloner(&entry.borrow())))
+            .collect::<SecondaryMap<EntityId, T>>();
+
+        Some(Box::new(entries))
+    }
+
+    fn restore(&mut self, snapshot: Option<&dyn Any>) {
+        self.components.clear();
+
+        let (Some(cloner), Some(snapshot)) = (self.cloner, snapshot) else {
+            return;
+        };
+        let entries = snapshot
+            .downcast_ref::<SecondaryMap<EntityId, T>>()
+            .unwrap();
+        for (key, entry) in entries.iter() {
+            self.components.insert(key, RefCell::new(cloner(entry)));
+        }
+    }
+
+    fn retain_entries(&mut self, keep: &dyn Fn(EntityId) -> bool) {
+        self.components.retain(keep);
+    }
+
+    fn take_entry(&mut self, key: EntityId) -> Option<Box<dyn Any>> {
+        self.remove(key).map(|entry| Box::new(entry) as Box<dyn Any>)
+    }
+
+    fn insert_entry(&mut self, key: EntityId, entry: Box<dyn Any>) {
+        self.insert(key, *entry.downcast::<T>().unwrap());
+    }
+
+    fn empty(&self) -> Box<dyn Storage> {
+        Box::new(Self {
+            components: Entries::new(self.kind()),
+            cloner: self.cloner,
+        })
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn add_trigger(&self) -> HookFn {
+        trigger_on_add::<T>
+    }
+
+    fn remove_trigger(&self) -> HookFn {
+        trigger_on_remove::<T>
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
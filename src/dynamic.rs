@@ -0,0 +1,300 @@
+use crate::{
+    component::{Component, ComponentStorage, EntityId},
+    world::World,
+};
+use slotmap::SecondaryMap;
+use std::{
+    alloc::Layout,
+    any::TypeId,
+    cell::{Ref, RefCell, RefMut},
+    collections::HashMap,
+};
+
+/// Identifies a component type, whether it is a Rust type or was registered at runtime with `World::register_dynamic`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComponentId(Kind);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    // keyed like `World::components`
+    Static(TypeId),
+    // index into `DynamicComponents::storages`
+    Dynamic(usize),
+}
+
+/// Describes a component type that has no Rust type, e.g. one defined by a mod.
+#[derive(Debug, Clone)]
+pub struct ComponentDescriptor {
+    name: String,
+    layout: Layout,
+    drop: Option<fn(&mut [u8])>,
+}
+
+impl ComponentDescriptor {
+    /// Describes a component of plain bytes with the given size and alignment.
+    pub fn new(name: impl Into<String>, layout: Layout) -> Self {
+        Self {
+            name: name.into(),
+            layout,
+            drop: None,
+        }
+    }
+
+    /// Sets a function called with the bytes of every value of the component right before it is dropped.
+    pub fn with_drop(mut self, drop: fn(&mut [u8])) -> Self {
+        self.drop = Some(drop);
+
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+/// The bytes of a single dynamic component, aligned as its layout requires.
+struct DynamicValue {
+    // over-allocated by `align - 1` bytes so the value can start at an aligned offset
+    buffer: Box<[u8]>,
+    offset: usize,
+    size: usize,
+    drop: Option<fn(&mut [u8])>,
+}
+
+impl DynamicValue {
+    fn new(descriptor: &ComponentDescriptor, bytes: &[u8]) -> Self {
+        let size = descriptor.layout.size();
+        let align = descriptor.layout.align();
+
+        let buffer = vec![0; size + align - 1].into_boxed_slice();
+        let offset = buffer.as_ptr().align_offset(align);
+        let mut value = Self {
+            buffer,
+            offset,
+            size,
+            drop: descriptor.drop,
+        };
+        value.bytes_mut().copy_from_slice(bytes);
+
+        value
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.buffer[self.offset..self.offset + self.size]
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer[self.offset..self.offset + self.size]
+    }
+}
+
+impl Drop for DynamicValue {
+    fn drop(&mut self) {
+        if let Some(drop) = self.drop {
+            drop(self.bytes_mut());
+        }
+    }
+}
+
+struct DynamicStorage {
+    descriptor: ComponentDescriptor,
+    values: SecondaryMap<EntityId, RefCell<DynamicValue>>,
+}
+
+/// Storage of every component registered with `World::register_dynamic`.
+#[derive(Default)]
+pub(crate) struct DynamicComponents {
+    storages: Vec<DynamicStorage>,
+    by_name: HashMap<String, usize>,
+}
+
+/// Drops every dynamic component of a removed entity. Registered as a cleanup by `World::new`.
+pub(crate) fn remove_dynamic_components(world: &mut World, key: EntityId) {
+    for storage in world.dynamic.storages.iter_mut() {
+        storage.values.remove(key);
+    }
+}
+
+/// Entities whose components match a set of `ComponentId`s, mixing Rust and dynamic component types.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicQuery {
+    with: Vec<ComponentId>,
+    without: Vec<ComponentId>,
+}
+
+impl DynamicQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches entities that have the component `id`.
+    pub fn with(mut self, id: ComponentId) -> Self {
+        self.with.push(id);
+
+        self
+    }
+
+    /// Only matches entities that do not have the component `id`.
+    pub fn without(mut self, id: ComponentId) -> Self {
+        self.without.push(id);
+
+        self
+    }
+
+    /// Iterates over the matching entities. Their components can then be read with `World::get_component`
+    /// or `World::get_component_by_id`.
+    pub fn iter<'a>(&'a self, world: &'a World) -> impl Iterator<Item = EntityId> + 'a {
+        world.query::<EntityId>().map(|id| *id).filter(|&key| {
+            self.with
+                .iter()
+                .all(|&id| world.contains_component_by_id(key, id))
+                && !self
+                    .without
+                    .iter()
+                    .any(|&id| world.contains_component_by_id(key, id))
+        })
+    }
+}
+
+/// Dynamic component operations, for component types defined at runtime that are accessed as raw bytes.
+///
+/// Dynamic components are removed with their entity, but have no hooks, observers or removal tracking,
+/// and are not part of the `TypeRegistry`, cloning or serialization.
+/// Rust component types can be checked for by `ComponentId`, e.g. in a `DynamicQuery`, but their values are
+/// never exposed as bytes, since their layout is not stable and they may hold pointers.
+/// A dynamic `ComponentId` that was not registered with this world, e.g. one from another world, is treated as a component
+/// no entity has.
+impl World {
+    /// Registers a component type without a Rust type, returning its `ComponentId`.
+    /// # Panics
+    /// Panics if a dynamic component with the same name was already registered.
+    pub fn register_dynamic(&mut self, descriptor: ComponentDescriptor) -> ComponentId {
+        assert!(
+            !self.dynamic.by_name.contains_key(&descriptor.name),
+            "Dynamic component `{}` is already registered",
+            descriptor.name
+        );
+
+        let index = self.dynamic.storages.len();
+        self.dynamic.by_name.insert(descriptor.name.clone(), index);
+        self.dynamic.storages.push(DynamicStorage {
+            descriptor,
+            values: SecondaryMap::new(),
+        });
+
+        ComponentId(Kind::Dynamic(index))
+    }
+
+    /// Gets the `ComponentId` of a registered Rust component type.
+    pub fn component_id<T>(&self) -> Option<ComponentId>
+    where
+        T: Component + 'static,
+    {
+        self.contains_storage::<T>()
+            .then(|| ComponentId(Kind::Static(TypeId::of::<ComponentStorage<T>>())))
+    }
+
+    /// Gets the `ComponentId` of a dynamic component by the name it was registered with.
+    pub fn dynamic_component_id(&self, name: &str) -> Option<ComponentId> {
+        self.dynamic
+            .by_name
+            .get(name)
+            .map(|&index| ComponentId(Kind::Dynamic(index)))
+    }
+
+    /// Gets the descriptor of a dynamic component. Returns `None` for Rust component types.
+    pub fn component_descriptor(&self, id: ComponentId) -> Option<&ComponentDescriptor> {
+        Some(&self.dynamic_storage(id)?.descriptor)
+    }
+
+    fn dynamic_storage(&self, id: ComponentId) -> Option<&DynamicStorage> {
+        match id.0 {
+            Kind::Static(_) => None,
+            Kind::Dynamic(index) => self.dynamic.storages.get(index),
+        }
+    }
+
+    fn dynamic_storage_mut(&mut self, id: ComponentId) -> Option<&mut DynamicStorage> {
+        match id.0 {
+            Kind::Static(_) => None,
+            Kind::Dynamic(index) => self.dynamic.storages.get_mut(index),
+        }
+    }
+
+    /// Checks if an entity has the component `id`, which can be a Rust or a dynamic component type.
+    pub fn contains_component_by_id(&self, key: EntityId, id: ComponentId) -> bool {
+        match id.0 {
+            Kind::Static(type_id) => self.contains_entry(type_id, key),
+            Kind::Dynamic(_) => self
+                .dynamic_storage(id)
+                .is_some_and(|storage| storage.values.contains_key(key)),
+        }
+    }
+
+    /// Adds a dynamic component to an entity, copying its value from `bytes`.
+    /// Returns `true` if the entity already had the component, whose old value is dropped.
+    /// Nothing is added, and `false` is returned, if `id` was not registered with this world.
+    /// # Panics
+    /// Panics if `id` is not a dynamic component, if the entity does not exist,
+    /// or if `bytes` does not match the size of the component's layout.
+    pub fn add_component_by_id(&mut self, key: EntityId, id: ComponentId, bytes: &[u8]) -> bool {
+        assert!(
+            self.contains_component::<EntityId>(key),
+            "Entity does not exist"
+        );
+        assert!(
+            matches!(id.0, Kind::Dynamic(_)),
+            "Only dynamic components can be added by id"
+        );
+        let Some(storage) = self.dynamic_storage_mut(id) else {
+            return false;
+        };
+        assert!(
+            bytes.len() == storage.descriptor.layout.size(),
+            "Expected {} bytes for dynamic component `{}`, got {}",
+            storage.descriptor.layout.size(),
+            storage.descriptor.name,
+            bytes.len()
+        );
+
+        let value = DynamicValue::new(&storage.descriptor, bytes);
+        storage.values.insert(key, RefCell::new(value)).is_some()
+    }
+
+    /// Removes a dynamic component from an entity, dropping it. Returns `true` if the entity had the component.
+    /// # Panics
+    /// Panics if `id` is not a dynamic component.
+    pub fn remove_component_by_id(&mut self, key: EntityId, id: ComponentId) -> bool {
+        assert!(
+            matches!(id.0, Kind::Dynamic(_)),
+            "Only dynamic components can be removed by id"
+        );
+
+        self.dynamic_storage_mut(id)
+            .is_some_and(|storage| storage.values.remove(key).is_some())
+    }
+
+    /// Gets the bytes of a dynamic component of an entity.
+    /// Returns `None` for Rust component types, which are read with `World::get_component` instead.
+    pub fn get_component_by_id(&self, key: EntityId, id: ComponentId) -> Option<Ref<'_, [u8]>> {
+        let value = self.dynamic_storage(id)?.values.get(key)?;
+
+        Some(Ref::map(value.borrow(), DynamicValue::bytes))
+    }
+
+    /// Gets the bytes of a dynamic component of an entity for modification.
+    /// Returns `None` for Rust component types, which are modified with `World::get_component_mut` instead.
+    pub fn get_component_by_id_mut(
+        &self,
+        key: EntityId,
+        id: ComponentId,
+    ) -> Option<RefMut<'_, [u8]>> {
+        let value = self.dynamic_storage(id)?.values.get(key)?;
+
+        Some(RefMut::map(value.borrow_mut(), DynamicValue::bytes_mut))
+    }
+}
@@ -1,10 +1,13 @@
 #![allow(unused)]
+use crate::bundle::Bundle;
 use crate::component::{Component, EntityId};
+use crate::prefab::PrefabLink;
 use crate::world::World;
 
 pub struct EntityBuilder<'a> {
     id: EntityId,
     world: &'a mut World,
+    linked: bool,
 }
 
 /// An entity builder used to create and configure entities within a `World`.
@@ -12,15 +15,54 @@ impl<'a> EntityBuilder<'a> {
     pub fn new(id: EntityId, world: &'a mut World) -> Self {
         world.add_component(id, id);
 
-        Self { id, world }
+        Self {
+            id,
+            world,
+            linked: false,
+        }
+    }
+
+    /// Creates a builder for an entity that already exists, e.g. one spawned from a prefab, without adding its `EntityId` again.
+    pub(crate) fn existing(id: EntityId, world: &'a mut World) -> Self {
+        Self {
+            id,
+            world,
+            linked: false,
+        }
+    }
+
+    /// Like `EntityBuilder::existing`, for an entity created by `World::instantiate_linked`, which records every added component
+    /// as an override.
+    pub(crate) fn linked(id: EntityId, world: &'a mut World) -> Self {
+        Self {
+            id,
+            world,
+            linked: true,
+        }
     }
 
     /// Adds a component to the entity being built.
+    /// If the builder was returned by `World::instantiate_linked`, the component is recorded as an override of the prefab's value.
     pub fn with<T>(&mut self, entry: T) -> &mut Self
     where
         T: Component + 'static,
     {
         self.world.add_component(self.id, entry);
+        if self.linked {
+            if let Some(mut link) = self.world.get_component_mut::<PrefabLink>(self.id) {
+                link.add_override::<T>();
+            }
+        }
+
+        self
+    }
+
+    /// Adds every component of a bundle to the entity being built, like calling `EntityBuilder::with` for each of them.
+    pub fn with_bundle<B>(&mut self, bundle: B) -> &mut Self
+    where
+        B: Bundle,
+    {
+        bundle.add_to(self);
 
         self
     }
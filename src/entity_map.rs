@@ -0,0 +1,281 @@
+use crate::{
+    component::{Component, ComponentStorage, EntityId},
+    hierarchy::{Children, Parent},
+    registry::ComponentInfo,
+    world::{managed_storages, World},
+};
+use std::{any::TypeId, collections::HashMap};
+
+/// Maps entity IDs from one world, or from a file, to the IDs of the matching entities in another world.
+#[derive(Debug, Clone, Default)]
+pub struct EntityMap {
+    map: HashMap<EntityId, EntityId>,
+}
+
+impl EntityMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `from` corresponds to `to`, returning the previous mapping of `from` if there was one.
+    pub fn insert(&mut self, from: EntityId, to: EntityId) -> Option<EntityId> {
+        self.map.insert(from, to)
+    }
+
+    /// Gets the entity `from` corresponds to, if it was mapped.
+    pub fn get(&self, from: EntityId) -> Option<EntityId> {
+        self.map.get(&from).copied()
+    }
+
+    /// Gets the entity `from` corresponds to. IDs that were not mapped become the null ID, since they would otherwise point at an unrelated entity.
+    pub fn map(&self, from: EntityId) -> EntityId {
+        self.get(from).unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Iterates over all `(from, to)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, EntityId)> + '_ {
+        self.map.iter().map(|(from, to)| (*from, *to))
+    }
+}
+
+/// Implemented by components that store entity IDs, so the IDs can be updated when entities are loaded or moved between worlds.
+///
+/// Components are remapped once registered with `ComponentRegistration::map_entities`. Resources are only remapped when loaded
+/// by `World::deserialize` after being registered with `World::register_serializable_resource_with_entities`; they are never
+/// remapped when entities are copied or moved between worlds.
+pub trait MapEntities {
+    /// Replaces every entity ID inside `self` using `map`.
+    fn map_entities(&mut self, map: &EntityMap);
+}
+
+impl MapEntities for EntityId {
+    fn map_entities(&mut self, map: &EntityMap) {
+        *self = map.map(*self);
+    }
+}
+
+impl MapEntities for Parent {
+    fn map_entities(&mut self, map: &EntityMap) {
+        self.0.map_entities(map);
+    }
+}
+
+impl MapEntities for Children {
+    fn map_entities(&mut self, map: &EntityMap) {
+        for child in self.0.iter_mut() {
+            child.map_entities(map);
+        }
+    }
+}
+
+/// Remaps the `T` component of an entity. Stored per type by `ComponentRegistration::map_entities`.
+pub(crate) type MapEntitiesFn = fn(&World, EntityId, &EntityMap);
+
+fn map_component_entities<T>(world: &World, key: EntityId, map: &EntityMap)
+where
+    T: Component + MapEntities + 'static,
+{
+    if let Some(mut component) = world.get_component_mut::<T>(key) {
+        component.map_entities(map);
+    }
+}
+
+/// Entity mapping operations.
+impl World {
+    /// Sets `T` to be remapped by `World::map_entities`. Used by `ComponentRegistration::map_entities`.
+    pub(crate) fn add_entity_mapper<T>(&mut self)
+    where
+        T: Component + MapEntities + 'static,
+    {
+        self.entity_mappers.insert(
+            TypeId::of::<ComponentStorage<T>>(),
+            map_component_entities::<T>,
+        );
+    }
+
+    /// Remaps the entity IDs stored in every component of `key` whose type was registered with `ComponentRegistration::map_entities`.
+    pub fn map_entities(&self, key: EntityId, map: &EntityMap) {
+        for mapper in self.entity_mappers.values() {
+            mapper(self, key, map);
+        }
+    }
+
+    /// Copies entities from another world into this one, returning a map from their IDs in `source` to the new IDs.
+    ///
+    /// Components registered with `World::register_cloneable` in both worlds are cloned, then remapped with `World::map_entities`.
+    /// Parent-child links between the copied entities are kept; links to entities that were not copied are dropped.
+    /// Hooks, required components and observers of the copies run once every copy is remapped and linked.
+    pub fn copy_entities(&mut self, source: &World, entities: &[EntityId]) -> EntityMap {
+        let mut map = EntityMap::new();
+        // in the order they were given, so children keep their order
+        let mut copied = Vec::new();
+        for &from in entities {
+            if source.contains_component::<EntityId>(from) && map.get(from).is_none() {
+                let to = self.new_entity().build();
+                map.insert(from, to);
+                copied.push((from, to));
+            }
+        }
+
+        // the hierarchy is linked below instead
+        let managed = managed_storages();
+        let mut added = Vec::new();
+        for &(from, to) in copied.iter() {
+            let components = source
+                .registry
+                .iter()
+                .filter(|info| !managed.contains(&info.storage_id))
+                .filter(|info| self.registry.get_by_type_id(info.type_id()).is_some())
+                .filter_map(|info| {
+                    let value = (info.get)(source, from)?;
+
+                    Some((info.storage_id, info.clone_value(&*value)?))
+                })
+                .collect::<Vec<_>>();
+
+            // hooks run below, once the copies are remapped
+            for (storage_id, component) in components {
+                if let Some(storage) = self.components.get_mut(&storage_id) {
+                    storage.insert_entry(to, component);
+                    added.push((to, storage_id));
+                }
+            }
+        }
+
+        // remap before linking the hierarchy, since `Parent` and `Children` are remapped too
+        for &(_, to) in copied.iter() {
+            self.map_entities(to, &map);
+        }
+        for &(from, to) in copied.iter() {
+            if let Some(parent) = source.parent(from).and_then(|parent| map.get(parent)) {
+                self.add_child(parent, to);
+            }
+        }
+        for (to, storage_id) in added {
+            self.component_added(storage_id, to);
+        }
+
+        map
+    }
+}
+
+/// Moving entities between worlds, e.g. to stream in level chunks built on a background world.
+impl World {
+    /// Moves an entity and its descendants into `other`, returning the entity's new ID. The entity is removed from this world,
+    /// and becomes a root in `other`.
+    ///
+    /// Every component of a registered type is moved, including components that are not cloneable, then remapped with
+    /// `World::map_entities`. Component types missing in `other` are registered there first, with the same hooks, required
+    /// components, entity mappers, cleanups and type registry entry. In `other`, the moved components add the components they
+    /// require, run their `on_add` and `on_insert` hooks and fire `OnAdd` observers, like with `World::clone_entity`; in this world
+    /// they are not treated as removed.
+    /// Dynamic components are dropped, and IDs of the moved entities stored by entities that stay behind are not updated.
+    /// # Panics
+    /// Panics if the entity does not exist.
+    pub fn transfer_entity(&mut self, key: EntityId, other: &mut World) -> EntityId {
+        assert!(self.ids.contains_key(key), "Entity {key:?} does not exist");
+
+        let moved = [key]
+            .into_iter()
+            .chain(self.descendants(key))
+            .collect::<Vec<_>>();
+        let map = self.move_entities(&moved, other);
+        self.remove_entity(key);
+
+        map.map(key)
+    }
+
+    /// Moves every entity of `other` into this world, returning a map from their IDs in `other` to the new IDs.
+    /// Components are moved like with `World::transfer_entity`, and the hierarchy is kept. Resources are dropped.
+    pub fn merge(&mut self, mut other: World) -> EntityMap {
+        let roots = other
+            .ids
+            .keys()
+            .filter(|key| other.parent(*key).is_none())
+            .collect::<Vec<_>>();
+        let moved = roots
+            .into_iter()
+            .flat_map(|root| [root].into_iter().chain(other.descendants(root)))
+            .collect::<Vec<_>>();
+
+        other.move_entities(&moved, self)
+    }
+
+    /// Moves the components of `entities` into new entities of `other`. Parents must come before their children.
+    /// The entities are left in this world without components, apart from their IDs and hierarchy.
+    fn move_entities(&mut self, entities: &[EntityId], other: &mut World) -> EntityMap {
+        // the hierarchy is linked below instead
+        let managed = managed_storages();
+        let infos = self
+            .registry
+            .iter()
+            .filter(|info| !managed.contains(&info.storage_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        for info in infos.iter() {
+            other.register_like(self, info);
+        }
+
+        let mut map = EntityMap::new();
+        let mut moved = Vec::new();
+        for &from in entities {
+            let to = other.new_entity().build();
+            map.insert(from, to);
+
+            for info in infos.iter() {
+                let storage = self.components.get_mut(&info.storage_id).unwrap();
+                if let Some(component) = storage.take_entry(from) {
+                    let storage = other.components.get_mut(&info.storage_id).unwrap();
+                    storage.insert_entry(to, component);
+                    moved.push((to, info.storage_id));
+                }
+            }
+        }
+
+        // remap before linking the hierarchy, since `Parent` and `Children` are remapped too
+        for &from in entities {
+            other.map_entities(map.map(from), &map);
+        }
+        for &from in entities {
+            if let Some(parent) = self.parent(from).and_then(|parent| map.get(parent)) {
+                other.add_child(parent, map.map(from));
+            }
+        }
+        for (to, storage_id) in moved {
+            other.component_added(storage_id, to);
+        }
+
+        map
+    }
+
+    /// Registers the component type described by `info` like it is registered in `source`, unless it is already registered.
+    fn register_like(&mut self, source: &World, info: &ComponentInfo) {
+        if self.components.contains_key(&info.storage_id) {
+            return;
+        }
+
+        let id = info.storage_id;
+        self.components.insert(id, source.components[&id].empty());
+        if let Some(hooks) = source.hooks.get(&id) {
+            self.hooks.insert(id, *hooks);
+        }
+        if let Some(required) = source.required.get(&id) {
+            self.required.insert(id, required.clone());
+        }
+        if let Some(mapper) = source.entity_mappers.get(&id) {
+            self.entity_mappers.insert(id, *mapper);
+        }
+        if let Some(cleanup) = source.storage_cleanups.get(&id) {
+            self.storage_cleanups.insert(id, *cleanup);
+        }
+        self.registry.add_info(info.clone());
+    }
+}
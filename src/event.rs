@@ -0,0 +1,205 @@
+use crate::{component::Component, world::World};
+use std::{cell::RefMut, marker::PhantomData};
+
+/// A double-buffered queue of events of type `E`, stored as a resource. Added with `App::add_event`.
+///
+/// Events stay readable for the frame they were sent in and the frame after, then are dropped by `Events::update`.
+pub struct Events<E>
+where
+    E: 'static,
+{
+    previous: Vec<E>,
+    current: Vec<E>,
+    // id of the first event in `previous`
+    previous_start: usize,
+}
+impl<E> Component for Events<E> where E: 'static {}
+
+impl<E> Default for Events<E>
+where
+    E: 'static,
+{
+    fn default() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            previous_start: 0,
+        }
+    }
+}
+
+impl<E> Events<E>
+where
+    E: 'static,
+{
+    /// Sends an event, making it visible to every `EventReader`.
+    pub fn send(&mut self, event: E) {
+        self.current.push(event);
+    }
+
+    /// Swaps the buffers, dropping events sent two updates ago. Called once per frame by `App::run`.
+    pub fn update(&mut self) {
+        self.previous_start += self.previous.len();
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// Creates a reader that sees every event still buffered, followed by every event sent afterwards.
+    pub fn get_reader(&self) -> EventReader<E> {
+        EventReader::default()
+    }
+
+    /// Creates a reader that only sees events sent after this call.
+    pub fn get_reader_current(&self) -> EventReader<E> {
+        EventReader {
+            cursor: self.event_count(),
+            marker: PhantomData,
+        }
+    }
+
+    /// The total number of events sent since the `Events` was created.
+    pub fn event_count(&self) -> usize {
+        self.previous_start + self.previous.len() + self.current.len()
+    }
+
+    /// Checks if no events are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty() && self.current.is_empty()
+    }
+
+    /// Drops every buffered event.
+    pub fn clear(&mut self) {
+        self.update();
+        self.update();
+    }
+
+    /// System that updates `Events<E>`. Registered by `App::add_event`.
+    pub fn update_system(world: &mut World) {
+        if let Some(mut events) = world.get_resource_mut::<Events<E>>() {
+            events.update();
+        }
+    }
+}
+
+/// Reads events of type `E`, tracking which ones it has already seen.
+///
+/// Each reader has its own cursor, so every reader sees every event exactly once as long as it reads at least once per frame.
+/// Keep the reader in a resource or component so the cursor survives between frames.
+pub struct EventReader<E>
+where
+    E: 'static,
+{
+    cursor: usize,
+    marker: PhantomData<fn() -> E>,
+}
+impl<E> Component for EventReader<E> where E: 'static {}
+
+impl<E> Default for EventReader<E>
+where
+    E: 'static,
+{
+    fn default() -> Self {
+        Self {
+            cursor: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> Clone for EventReader<E>
+where
+    E: 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            cursor: self.cursor,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> EventReader<E>
+where
+    E: 'static,
+{
+    /// Iterates over the events this reader has not seen yet, oldest first, and marks them as seen.
+    pub fn read<'a>(&mut self, events: &'a Events<E>) -> impl Iterator<Item = &'a E> {
+        let skip = self.cursor.saturating_sub(events.previous_start);
+        self.cursor = events.event_count();
+
+        events
+            .previous
+            .iter()
+            .chain(events.current.iter())
+            .skip(skip)
+    }
+
+    /// The number of events this reader has not seen yet.
+    pub fn len(&self, events: &Events<E>) -> usize {
+        events.event_count() - self.cursor.max(events.previous_start)
+    }
+
+    /// Checks if this reader has seen every buffered event.
+    pub fn is_empty(&self, events: &Events<E>) -> bool {
+        self.len(events) == 0
+    }
+}
+
+/// Sends events of type `E`. Created with `World::event_writer`.
+pub struct EventWriter<'a, E>
+where
+    E: 'static,
+{
+    events: RefMut<'a, Events<E>>,
+}
+
+impl<'a, E> EventWriter<'a, E>
+where
+    E: 'static,
+{
+    /// Sends an event, making it visible to every `EventReader`.
+    pub fn send(&mut self, event: E) {
+        self.events.send(event);
+    }
+
+    /// Sends every event from an iterator.
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        self.events.current.extend(events);
+    }
+}
+
+/// Event operations.
+impl World {
+    /// Gets an `EventWriter` for events of type `E`.
+    /// # Panics
+    /// Panics if the event `E` has not been added with `App::add_event` or `World::add_event`.
+    pub fn event_writer<E>(&self) -> EventWriter<'_, E>
+    where
+        E: 'static,
+    {
+        let events = self
+            .get_resource_mut::<Events<E>>()
+            .unwrap_or_else(|| panic!("Event {} is not added", std::any::type_name::<E>()));
+
+        EventWriter { events }
+    }
+
+    /// Sends a single event of type `E`.
+    /// # Panics
+    /// Panics if the event `E` has not been added with `App::add_event` or `World::add_event`.
+    pub fn send_event<E>(&self, event: E)
+    where
+        E: 'static,
+    {
+        self.event_writer::<E>().send(event);
+    }
+
+    /// Adds the `Events<E>` resource if it does not exist yet. It is not updated automatically; prefer `App::add_event`.
+    pub fn add_event<E>(&mut self)
+    where
+        E: 'static,
+    {
+        if self.get_resource::<Events<E>>().is_none() {
+            self.add_resource(Events::<E>::default());
+        }
+    }
+}
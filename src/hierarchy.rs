@@ -0,0 +1,139 @@
+use crate::{
+    component::{Component, EntityId},
+    world::World,
+};
+
+/// Points to the parent of an entity. This component is managed by `World`; use `World::set_parent` or `World::add_child` instead of inserting it directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parent(pub EntityId);
+impl Component for Parent {}
+
+/// Lists the children of an entity in insertion order. This component is managed by `World`; use `World::add_child` or `World::remove_child` instead of inserting it directly.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Children(pub Vec<EntityId>);
+impl Component for Children {}
+
+/// Depth-first iterator over every descendant of an entity. Created by `World::descendants`.
+pub struct Descendants<'a> {
+    world: &'a World,
+    stack: Vec<EntityId>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = EntityId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.stack.pop()?;
+
+        if let Some(children) = self.world.get_component::<Children>(key) {
+            self.stack.extend(children.0.iter().rev());
+        }
+
+        Some(key)
+    }
+}
+
+/// Iterator walking up the parent chain of an entity, nearest parent first. Created by `World::ancestors`.
+pub struct Ancestors<'a> {
+    world: &'a World,
+    current: Option<EntityId>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = EntityId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let parent = self.world.parent(self.current?);
+        self.current = parent;
+
+        parent
+    }
+}
+
+/// Hierarchy operations. `World` keeps `Parent` and `Children` consistent with each other.
+impl World {
+    /// Makes `child` a child of `parent`, detaching it from its previous parent if it had one.
+    /// # Panics
+    /// Panics if `child` is `parent` or one of its ancestors, since that would create a cycle.
+    pub fn add_child(&mut self, parent: EntityId, child: EntityId) {
+        assert!(
+            parent != child && !self.ancestors(parent).any(|id| id == child),
+            "Adding {child:?} as a child of {parent:?} would create a cycle"
+        );
+
+        self.remove_parent(child);
+        self.add_component(child, Parent(parent));
+
+        let has_children = self
+            .get_component_mut::<Children>(parent)
+            .map(|mut children| children.0.push(child))
+            .is_some();
+        if !has_children {
+            self.add_component(parent, Children(vec![child]));
+        }
+    }
+
+    /// Detaches `child` from `parent`. The child is kept alive and becomes a root entity.
+    /// Does nothing if `child` is not a child of `parent`.
+    pub fn remove_child(&mut self, parent: EntityId, child: EntityId) {
+        if self.parent(child) == Some(parent) {
+            self.remove_parent(child);
+        }
+    }
+
+    /// Sets the parent of an entity, or makes it a root entity when `parent` is `None`.
+    pub fn set_parent(&mut self, child: EntityId, parent: Option<EntityId>) {
+        match parent {
+            Some(parent) => self.add_child(parent, child),
+            None => self.remove_parent(child),
+        }
+    }
+
+    /// Gets the parent of an entity if it has one.
+    pub fn parent(&self, key: EntityId) -> Option<EntityId> {
+        self.get_component::<Parent>(key).map(|parent| parent.0)
+    }
+
+    /// Gets the direct children of an entity. Returns an empty `Vec` if it has none.
+    pub fn children(&self, key: EntityId) -> Vec<EntityId> {
+        self.get_component::<Children>(key)
+            .map(|children| children.0.clone())
+            .unwrap_or_default()
+    }
+
+    /// Iterates over all descendants of an entity, depth first. The entity itself is not included.
+    pub fn descendants(&self, key: EntityId) -> Descendants<'_> {
+        let mut stack = self.children(key);
+        stack.reverse();
+
+        Descendants { world: self, stack }
+    }
+
+    /// Iterates over all ancestors of an entity, starting with its parent and ending with the root.
+    pub fn ancestors(&self, key: EntityId) -> Ancestors<'_> {
+        Ancestors {
+            world: self,
+            current: Some(key),
+        }
+    }
+
+    /// Removes the `Parent` of an entity and unlinks it from the parent's `Children`.
+    pub(crate) fn remove_parent(&mut self, child: EntityId) {
+        let Some(Parent(parent)) = self.remove_component::<Parent>(child) else {
+            return;
+        };
+
+        let now_empty = self
+            .get_component_mut::<Children>(parent)
+            .map(|mut children| {
+                children.0.retain(|&id| id != child);
+                children.0.is_empty()
+            })
+            .unwrap_or(false);
+        if now_empty {
+            self.remove_component::<Children>(parent);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{
+    component::{Component, ComponentStorage, EntityId, StorageKind},
+    entity_map::MapEntities,
+    world::World,
+};
+use std::{any::TypeId, fmt::Debug, marker::PhantomData};
+
+/// A function called with the world and the entity whose component changed.
+pub type HookFn = fn(&mut World, EntityId);
+
+/// Inserts a required component into an entity that lacks it. Stored per component type by `ComponentRegistration::requires`,
+/// along with the `TypeId` of the required component.
+pub(crate) type RequireFn = fn(&mut World, EntityId);
+
+fn insert_required<R>(world: &mut World, key: EntityId)
+where
+    R: Component + Default + 'static,
+{
+    if !world.contains_component::<R>(key) {
+        world.add_component(key, R::default());
+    }
+}
+
+/// Lifecycle hooks of a single component type.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ComponentHooks {
+    /// Called after the component is added to an entity that did not have it.
+    pub on_add: Option<HookFn>,
+    /// Called after every insertion of the component, including ones that replace a previous value.
+    pub on_insert: Option<HookFn>,
+    /// Called before the component is removed from an entity, including when the entity itself is removed.
+    /// The component can still be read from inside the hook.
+    pub on_remove: Option<HookFn>,
+}
+
+/// Returned by `World::register` and `World::register_cloneable` to configure the registered component type.
+pub struct ComponentRegistration<'a, T>
+where
+    T: Component + 'static,
+{
+    world: &'a mut World,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T> ComponentRegistration<'a, T>
+where
+    T: Component + 'static,
+{
+    pub(crate) fn new(world: &'a mut World) -> Self {
+        Self {
+            world,
+            marker: PhantomData,
+        }
+    }
+
+    fn hooks(&mut self) -> &mut ComponentHooks {
+        self.world
+            .hooks
+            .entry(TypeId::of::<ComponentStorage<T>>())
+            .or_default()
+    }
+
+    /// Sets the hook called after `T` is added to an entity that did not have it.
+    pub fn on_add(&mut self, hook: HookFn) -> &mut Self {
+        self.hooks().on_add = Some(hook);
+
+        self
+    }
+
+    /// Sets the hook called after every insertion of `T`.
+    pub fn on_insert(&mut self, hook: HookFn) -> &mut Self {
+        self.hooks().on_insert = Some(hook);
+
+        self
+    }
+
+    /// Sets the hook called before `T` is removed from an entity.
+    pub fn on_remove(&mut self, hook: HookFn) -> &mut Self {
+        self.hooks().on_remove = Some(hook);
+
+        self
+    }
+
+    /// Makes the stored `T` components copyable, like `World::register_cloneable`.
+    pub fn cloneable(&mut self) -> &mut Self
+    where
+        T: Clone,
+    {
+        self.world.storage_mut::<T>().unwrap().make_cloneable();
+        self.world.registry.set_clone::<T>();
+
+        self
+    }
+
+    /// Sets how the stored `T` components are laid out, keeping the current ones. Components are `StorageKind::Dense` by default.
+    pub fn storage(&mut self, kind: StorageKind) -> &mut Self {
+        self.world.storage_mut::<T>().unwrap().set_kind(kind);
+
+        self
+    }
+
+    /// Makes `World::map_entities` remap the entity IDs stored in `T`, e.g. when loading a saved world.
+    pub fn map_entities(&mut self) -> &mut Self
+    where
+        T: MapEntities,
+    {
+        self.world.add_entity_mapper::<T>();
+
+        self
+    }
+
+    /// Makes adding `T` to an entity also add `R::default()`, unless the entity already has an `R`. Requirements of `R`
+    /// are added in turn, so `T` can rely on a whole chain of components being present.
+    ///
+    /// Requirements are only checked when `T` is added, so a required component can still be removed afterwards.
+    /// Values given explicitly after `T`, e.g. with `EntityBuilder::with`, replace the defaults.
+    pub fn requires<R>(&mut self) -> &mut Self
+    where
+        R: Component + Default + 'static,
+    {
+        let required = self
+            .world
+            .required
+            .entry(TypeId::of::<ComponentStorage<T>>())
+            .or_default();
+        let id = TypeId::of::<ComponentStorage<R>>();
+        if !required.iter().any(|(other, _)| *other == id) {
+            required.push((id, insert_required::<R>));
+        }
+
+        self
+    }
+
+    /// Sets the stable name `T` is looked up by in the `TypeRegistry`, instead of its type name.
+    /// # Panics
+    /// Panics if another component already uses `name`.
+    pub fn named(&mut self, name: &'static str) -> &mut Self {
+        self.world.registry.set_name::<T>(name);
+
+        self
+    }
+
+    /// Makes `ComponentInfo::default_value` create values of `T`.
+    pub fn reflect_default(&mut self) -> &mut Self
+    where
+        T: Default,
+    {
+        self.world.registry.set_default::<T>();
+
+        self
+    }
+
+    /// Makes `ComponentInfo::debug_value` and `World::debug_component` format values of `T`.
+    pub fn reflect_debug(&mut self) -> &mut Self
+    where
+        T: Debug,
+    {
+        self.world.registry.set_debug::<T>();
+
+        self
+    }
+
+    /// Makes `T` part of world serialization under the stable name `name`, which also becomes its name in the `TypeRegistry`.
+    /// # Panics
+    /// Panics if another component already uses `name`.
+    #[cfg(feature = "serde")]
+    pub fn serializable(&mut self, name: &'static str) -> &mut Self
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        self.world.registry.set_name::<T>(name);
+        self.world.registry.set_serde::<T>();
+
+        self
+    }
+}
@@ -1,8 +1,29 @@
 pub mod app;
+pub mod bundle;
 pub mod component;
+pub mod dynamic;
 pub mod entity_builder;
+pub mod entity_map;
+pub mod event;
+pub mod hierarchy;
+pub mod hooks;
 pub mod prelude;
+pub mod observer;
+pub mod plugin;
+pub mod prefab;
 pub mod query;
+pub mod registry;
+pub mod relation;
+pub mod removed;
+pub mod runner;
+#[cfg(feature = "serde")]
+pub mod scene;
 pub mod scheduler;
+pub mod snapshot;
+pub mod sub_app;
+pub mod system_param;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod transform;
 pub mod world;
 pub mod commands;
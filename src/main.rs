@@ -1,10 +1,31 @@
 mod app;
+mod bundle;
 mod component;
+mod dynamic;
 mod entity_builder;
+mod entity_map;
+mod event;
+mod hierarchy;
+mod hooks;
 mod prelude;
+mod observer;
+mod plugin;
+mod prefab;
 mod query;
+mod registry;
+mod relation;
+mod removed;
+mod runner;
+#[cfg(feature = "serde")]
+mod scene;
 mod scheduler;
+mod snapshot;
+mod sub_app;
+mod system_param;
+#[cfg(feature = "serde")]
+mod serialize;
 mod tests;
+mod transform;
 mod world;
 mod commands;
 use crate::prelude::*;
@@ -27,18 +48,22 @@ make_component! {
 }
 
 make_component! {
+    #[derive(Default)]
     struct Position(Vector2);
 }
 
 make_component! {
+    #[derive(Clone, Default)]
     struct Direction(Vector2);
 }
 
 make_component! {
+    #[derive(Clone)]
     struct Speed(f32);
 }
 
 make_component! {
+    #[derive(Clone)]
     struct Render(Color);
 }
 
@@ -47,10 +72,12 @@ make_component! {
 }
 
 make_component! {
+    #[derive(Clone)]
     struct Monster;
 }
 
 make_component! {
+    #[derive(Clone)]
     struct Health(f32);
 }
 
@@ -71,8 +98,8 @@ fn add_raylib(world: &mut World) {
 
 fn register_components(world: &mut World) {
     world.register::<Position>();
-    world.register::<Direction>();
-    world.register::<Speed>();
+    world.register::<Direction>().requires::<Position>();
+    world.register::<Speed>().requires::<Direction>();
     world.register::<Render>();
     world.register::<Player>();
     world.register::<Monster>();
@@ -82,8 +109,6 @@ fn register_components(world: &mut World) {
 fn add_player(world: &mut World) {
     let _player = world
         .new_entity()
-        .with(Position(Vector2::zero()))
-        .with(Direction(Vector2::zero()))
         .with(Speed(200.0))
         .with(Render(Color::BLUE))
         .with(Player)
@@ -92,21 +117,20 @@ fn add_player(world: &mut World) {
 }
 
 fn add_monsters(world: &mut World) {
+    let monster = Prefab::new()
+        .with(Direction(Vector2::zero()))
+        .with(Speed(100.0))
+        .with(Monster)
+        .with(Render(Color::RED))
+        .with(Health(MAX_HEALTH));
+
     for _ in 0..10 {
         let position = Position(Vector2::new(
             get_random_value::<i32>(0, TILE_X_COUNT) as f32 * TILE_SIZE,
             get_random_value::<i32>(0, TILE_Y_COUNT) as f32 * TILE_SIZE,
         ));
 
-        let _monster = world
-            .new_entity()
-            .with(position)
-            .with(Direction(Vector2::zero()))
-            .with(Speed(100.0))
-            .with(Monster)
-            .with(Render(Color::RED))
-            .with(Health(MAX_HEALTH))
-            .build();
+        let _monster = world.instantiate(&monster).with(position).build();
     }
 }
 
@@ -201,31 +225,71 @@ fn draw_system(world: &mut World) {
     d.draw_fps(0, 0);
 }
 
-fn close_system(world: &mut World) {
-    let shutdown = {
-        let rl = world.get_resource::<Handle>().unwrap();
+/// Runs frames until the window is closed. Raylib paces the frames itself, see `RaylibHandle::set_target_fps`.
+fn raylib_runner(app: &mut App) {
+    app.startup();
+
+    while app.world().active() {
+        let close = {
+            let rl = app.world().get_resource::<Handle>().unwrap();
+
+            rl.0.window_should_close()
+        };
+        if close {
+            app.world_mut().shutdown();
+            app.run_shutdown();
+            if !app.world().active() {
+                break;
+            }
+            // a shutdown system vetoed the exit, so the frame runs as usual
+        }
+        app.update();
+    }
+}
+
+/// Opens the window and runs the app until it is closed.
+struct RaylibPlugin;
 
-        rl.0.window_should_close()
-    };
+impl Plugin for RaylibPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(add_raylib).set_runner(raylib_runner);
+    }
+}
+
+/// The player, the monsters and the rules of the game.
+struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(register_components)
+            .add_startup_system(add_player)
+            .add_startup_system(add_monsters)
+            .add_system(change_player_velocity)
+            .add_system(change_monsters_velocity)
+            .add_system(change_color)
+            .add_system(hurt_monsters)
+            .add_system(cull_entities)
+            .add_system(move_system);
+    }
+
+    fn dependencies(&self) -> Vec<Box<dyn Plugin>> {
+        vec![Box::new(RaylibPlugin)]
+    }
+}
+
+/// Draws the game.
+struct RenderPlugin;
+
+impl Plugin for RenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(draw_system);
+    }
 
-    if shutdown {
-        world.shutdown();
+    fn dependencies(&self) -> Vec<Box<dyn Plugin>> {
+        vec![Box::new(RaylibPlugin)]
     }
 }
 
-fn main() {
-    App::new()
-        .add_startup_system(add_raylib)
-        .add_startup_system(register_components)
-        .add_startup_system(add_player)
-        .add_startup_system(add_monsters)
-        .add_system(change_player_velocity)
-        .add_system(change_monsters_velocity)
-        .add_system(change_color)
-        .add_system(hurt_monsters)
-        .add_system(cull_entities)
-        .add_system(move_system)
-        .add_system(draw_system)
-        .add_system(close_system)
-        .run();
+fn main() -> AppExit {
+    App::new().add_plugins((GamePlugin, RenderPlugin)).run()
 }
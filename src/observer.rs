@@ -0,0 +1,217 @@
+use crate::{
+    component::{Component, EntityId},
+    world::World,
+};
+use std::{
+    any::{Any, TypeId},
+    marker::PhantomData,
+};
+
+/// A function run immediately when a trigger of type `E` fires.
+pub type ObserverFn<E> = fn(&mut World, &mut Trigger<E>);
+
+/// The event passed to observers, along with the entity it targets.
+pub struct Trigger<E>
+where
+    E: 'static,
+{
+    event: E,
+    target: Option<EntityId>,
+    original_target: Option<EntityId>,
+    propagate: bool,
+}
+
+impl<E> Trigger<E>
+where
+    E: 'static,
+{
+    pub fn event(&self) -> &E {
+        &self.event
+    }
+
+    pub fn event_mut(&mut self) -> &mut E {
+        &mut self.event
+    }
+
+    /// The entity currently being visited. While propagating this is an ancestor of `Trigger::original_target`.
+    pub fn target(&self) -> Option<EntityId> {
+        self.target
+    }
+
+    /// The entity the trigger was sent to.
+    pub fn original_target(&self) -> Option<EntityId> {
+        self.original_target
+    }
+
+    /// Sets whether the trigger keeps propagating up the parent chain after the current entity.
+    pub fn propagate(&mut self, propagate: bool) {
+        self.propagate = propagate;
+    }
+}
+
+/// Triggered after a component `T` is added to an entity that did not have it.
+pub struct OnAdd<T>(PhantomData<fn() -> T>);
+
+/// Triggered before a component `T` is removed from an entity, including when the entity itself is removed.
+pub struct OnRemove<T>(PhantomData<fn() -> T>);
+
+struct ObserverList<E>
+where
+    E: 'static,
+{
+    // `None` targets observe every trigger of `E`
+    observers: Vec<(Option<EntityId>, ObserverFn<E>)>,
+}
+
+/// A type-erased `ObserverList<E>`.
+pub(crate) trait AnyObserverList {
+    fn remove_target(&mut self, key: EntityId);
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<E> AnyObserverList for ObserverList<E>
+where
+    E: 'static,
+{
+    fn remove_target(&mut self, key: EntityId) {
+        self.observers.retain(|(target, _)| *target != Some(key));
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Drops every observer targeted at a removed entity. Registered as a cleanup by `World::new`.
+pub(crate) fn remove_entity_observers(world: &mut World, key: EntityId) {
+    for list in world.observers.values_mut() {
+        list.remove_target(key);
+    }
+}
+
+/// Fires `OnAdd<T>` for an entity.
+pub(crate) fn trigger_on_add<T>(world: &mut World, key: EntityId)
+where
+    T: Component + 'static,
+{
+    world.trigger_with(OnAdd::<T>(PhantomData), Some(key), false);
+}
+
+/// Fires `OnRemove<T>` for an entity.
+pub(crate) fn trigger_on_remove<T>(world: &mut World, key: EntityId)
+where
+    T: Component + 'static,
+{
+    world.trigger_with(OnRemove::<T>(PhantomData), Some(key), false);
+}
+
+/// Observer operations. Observers run immediately when a trigger fires, instead of being polled every frame.
+impl World {
+    /// Adds an observer that runs for every trigger of type `E`, whatever its target.
+    pub fn observe<E>(&mut self, observer: ObserverFn<E>)
+    where
+        E: 'static,
+    {
+        self.add_observer(None, observer);
+    }
+
+    /// Adds an observer that only runs for triggers of type `E` targeting `key`, or propagating through it.
+    /// The observer is dropped when the entity is removed.
+    pub fn observe_entity<E>(&mut self, key: EntityId, observer: ObserverFn<E>)
+    where
+        E: 'static,
+    {
+        self.add_observer(Some(key), observer);
+    }
+
+    fn add_observer<E>(&mut self, target: Option<EntityId>, observer: ObserverFn<E>)
+    where
+        E: 'static,
+    {
+        self.observers
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| {
+                Box::new(ObserverList::<E> {
+                    observers: Vec::new(),
+                })
+            })
+            .as_any_mut()
+            .downcast_mut::<ObserverList<E>>()
+            .unwrap()
+            .observers
+            .push((target, observer));
+    }
+
+    /// Fires a trigger without a target, running every untargeted observer of `E`.
+    pub fn trigger<E>(&mut self, event: E)
+    where
+        E: 'static,
+    {
+        self.trigger_with(event, None, false);
+    }
+
+    /// Fires a trigger targeting `key`. Untargeted observers run first, then observers of `key`,
+    /// then the trigger propagates up the parent chain until it reaches a root or an observer calls `Trigger::propagate(false)`.
+    pub fn trigger_targets<E>(&mut self, event: E, key: EntityId)
+    where
+        E: 'static,
+    {
+        self.trigger_with(event, Some(key), true);
+    }
+
+    pub(crate) fn trigger_with<E>(&mut self, event: E, target: Option<EntityId>, propagate: bool)
+    where
+        E: 'static,
+    {
+        if !self.observers.contains_key(&TypeId::of::<E>()) {
+            return;
+        }
+
+        let mut trigger = Trigger {
+            event,
+            target,
+            original_target: target,
+            propagate,
+        };
+        self.run_observers(&mut trigger, None);
+
+        let mut current = target;
+        while let Some(key) = current {
+            trigger.target = Some(key);
+            self.run_observers(&mut trigger, Some(key));
+
+            if !trigger.propagate {
+                break;
+            }
+            current = self.parent(key);
+        }
+    }
+
+    fn run_observers<E>(&mut self, trigger: &mut Trigger<E>, target: Option<EntityId>)
+    where
+        E: 'static,
+    {
+        let observers = self
+            .observers
+            .get(&TypeId::of::<E>())
+            .and_then(|list| list.as_any().downcast_ref::<ObserverList<E>>())
+            .map(|list| {
+                list.observers
+                    .iter()
+                    .filter(|(observed, _)| *observed == target)
+                    .map(|(_, observer)| *observer)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        for observer in observers {
+            observer(self, trigger);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::app::App;
+use std::any::Any;
+
+/// A reusable bundle of app setup, e.g. the systems, events and startup systems of a physics or input module.
+pub trait Plugin: Any {
+    /// Sets up the plugin.
+    fn build(&self, app: &mut App);
+
+    /// Plugins that must be built before this one. They are added first, unless the app already has them.
+    fn dependencies(&self) -> Vec<Box<dyn Plugin>> {
+        Vec::new()
+    }
+
+    /// Whether adding this plugin twice is a mistake. Plugins that can be added many times, e.g. with different settings,
+    /// should return `false`.
+    fn is_unique(&self) -> bool {
+        true
+    }
+
+    /// The name of the plugin, for diagnostics.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+/// Several plugins added together with `App::add_plugins`, in order. Implemented for tuples of plugins.
+pub trait PluginGroup {
+    fn plugins(self) -> Vec<Box<dyn Plugin>>;
+}
+
+impl<P> PluginGroup for P
+where
+    P: Plugin,
+{
+    fn plugins(self) -> Vec<Box<dyn Plugin>> {
+        vec![Box::new(self)]
+    }
+}
+
+macro_rules! __impl_plugin_group {
+    ($($t:ident),*) => {
+        impl<$($t),*> PluginGroup for ($($t,)*)
+        where
+            $($t: PluginGroup,)*
+        {
+            #[allow(non_snake_case)]
+            fn plugins(self) -> Vec<Box<dyn Plugin>> {
+                let ($($t,)*) = self;
+                let mut plugins = Vec::new();
+                $(plugins.extend($t.plugins());)*
+
+                plugins
+            }
+        }
+    };
+}
+
+__impl_plugin_group!(T1);
+__impl_plugin_group!(T1, T2);
+__impl_plugin_group!(T1, T2, T3);
+__impl_plugin_group!(T1, T2, T3, T4);
+__impl_plugin_group!(T1, T2, T3, T4, T5);
+__impl_plugin_group!(T1, T2, T3, T4, T5, T6);
+__impl_plugin_group!(T1, T2, T3, T4, T5, T6, T7);
+__impl_plugin_group!(T1, T2, T3, T4, T5, T6, T7, T8);
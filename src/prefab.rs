@@ -0,0 +1,233 @@
+use crate::{
+    component::{Component, EntityId},
+    entity_builder::EntityBuilder,
+    world::World,
+};
+use std::{
+    any::TypeId,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+/// A type-erased component value stored in a `Prefab`.
+trait PrefabComponent {
+    fn insert(&self, world: &mut World, key: EntityId);
+}
+
+struct PrefabValue<T>(T);
+
+impl<T> PrefabComponent for PrefabValue<T>
+where
+    T: Component + Clone + 'static,
+{
+    fn insert(&self, world: &mut World, key: EntityId) {
+        world.add_component(key, self.0.clone());
+    }
+}
+
+#[derive(Default)]
+struct PrefabData {
+    // every value is stored with the version it was set at, so propagation can tell which values changed
+    components: Vec<(TypeId, u64, Box<dyn PrefabComponent>)>,
+    children: Vec<Prefab>,
+    version: u64,
+}
+
+/// A reusable entity template holding a set of component values and optional child prefabs.
+///
+/// `Prefab` is a shared handle: clones refer to the same template, so changes made with `Prefab::set` are seen by every clone
+/// and can be pushed to linked instances with `World::propagate_prefab`.
+#[derive(Clone, Default)]
+pub struct Prefab {
+    data: Rc<RefCell<PrefabData>>,
+}
+
+impl Prefab {
+    /// Creates an empty `Prefab`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a component value to the prefab, replacing any previous value of the same type.
+    pub fn with<T>(self, entry: T) -> Self
+    where
+        T: Component + Clone + 'static,
+    {
+        self.set(entry);
+
+        self
+    }
+
+    /// Adds a child prefab. Every instance gets its own instance of the child.
+    /// # Panics
+    /// Panics if `child` is this prefab or already contains it, since instantiating it would never end.
+    pub fn with_child(self, child: Prefab) -> Self {
+        assert!(!child.contains(&self), "Prefab cannot be a child of itself");
+        self.data.borrow_mut().children.push(child);
+
+        self
+    }
+
+    /// Checks if `other` is this prefab or one of its descendants.
+    fn contains(&self, other: &Prefab) -> bool {
+        self.ptr_eq(other)
+            || self
+                .data
+                .borrow()
+                .children
+                .iter()
+                .any(|child| child.contains(other))
+    }
+
+    /// Sets a component value on the prefab, replacing any previous value of the same type.
+    pub fn set<T>(&self, entry: T)
+    where
+        T: Component + Clone + 'static,
+    {
+        let id = TypeId::of::<T>();
+        let entry = Box::new(PrefabValue(entry));
+        let data = &mut *self.data.borrow_mut();
+        data.version += 1;
+
+        match data
+            .components
+            .iter_mut()
+            .find(|(type_id, ..)| *type_id == id)
+        {
+            Some((_, version, previous)) => {
+                *version = data.version;
+                *previous = entry;
+            }
+            None => data.components.push((id, data.version, entry)),
+        }
+    }
+
+    /// Checks if two handles refer to the same prefab.
+    pub fn ptr_eq(&self, other: &Prefab) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+
+    /// Inserts the prefab's components on `key`, skipping component types in `skip` and values no newer than those in `applied`.
+    /// Records the version of every inserted value in `applied`.
+    fn apply(
+        &self,
+        world: &mut World,
+        key: EntityId,
+        skip: &HashSet<TypeId>,
+        applied: &mut HashMap<TypeId, u64>,
+    ) {
+        let data = self.data.borrow();
+
+        for (id, version, component) in data.components.iter() {
+            if skip.contains(id) || applied.get(id) == Some(version) {
+                continue;
+            }
+
+            component.insert(world, key);
+            applied.insert(*id, *version);
+        }
+    }
+
+    fn spawn(&self, world: &mut World, linked: bool) -> EntityId {
+        let id = world.new_entity().build();
+        let mut applied = HashMap::new();
+
+        self.apply(world, id, &HashSet::new(), &mut applied);
+        if linked {
+            world.add_component(
+                id,
+                PrefabLink {
+                    prefab: self.clone(),
+                    overrides: HashSet::new(),
+                    applied,
+                },
+            );
+        }
+
+        let children = self.data.borrow().children.clone();
+        for child in children {
+            let child = child.spawn(world, linked);
+            world.add_child(id, child);
+        }
+
+        id
+    }
+}
+
+/// Links an instance to the `Prefab` it was created from. Added by `World::instantiate_linked`.
+#[derive(Clone)]
+pub struct PrefabLink {
+    prefab: Prefab,
+    // component types set per instance, which propagation must not overwrite
+    overrides: HashSet<TypeId>,
+    // the version of every prefab value last inserted on the instance
+    applied: HashMap<TypeId, u64>,
+}
+impl Component for PrefabLink {}
+
+impl PrefabLink {
+    /// The prefab the entity was created from.
+    pub fn prefab(&self) -> &Prefab {
+        &self.prefab
+    }
+
+    /// Marks a component type as overridden on this instance.
+    pub fn add_override<T>(&mut self)
+    where
+        T: Component + 'static,
+    {
+        self.overrides.insert(TypeId::of::<T>());
+    }
+
+    /// Checks if a component type is overridden on this instance.
+    pub fn is_overridden<T>(&self) -> bool
+    where
+        T: Component + 'static,
+    {
+        self.overrides.contains(&TypeId::of::<T>())
+    }
+}
+
+/// Prefab operations.
+impl World {
+    /// Creates a new entity from a prefab, along with entities for its child prefabs.
+    /// Components added through the returned `EntityBuilder` replace the prefab's values for this instance.
+    pub fn instantiate(&mut self, prefab: &Prefab) -> EntityBuilder<'_> {
+        let id = prefab.spawn(self, false);
+
+        EntityBuilder::existing(id, self)
+    }
+
+    /// Like `World::instantiate`, but keeps a `PrefabLink` on every created entity so later changes to the prefab can be applied with `World::propagate_prefab`.
+    /// Components added through the returned `EntityBuilder` are recorded as overrides and are never overwritten by propagation.
+    pub fn instantiate_linked(&mut self, prefab: &Prefab) -> EntityBuilder<'_> {
+        let id = prefab.spawn(self, true);
+
+        EntityBuilder::linked(id, self)
+    }
+
+    /// Applies the changes made to `prefab` since the last propagation to every entity linked to it.
+    ///
+    /// Only component types whose prefab value was set again are re-applied, replacing the instance's value, so changes made to
+    /// other components at runtime are kept. Overridden components are never re-applied.
+    pub fn propagate_prefab(&mut self, prefab: &Prefab) {
+        let instances = self
+            .query::<(EntityId, PrefabLink)>()
+            .filter(|(_, link)| link.prefab.ptr_eq(prefab))
+            .map(|(id, link)| (*id, link.overrides.clone(), link.applied.clone()))
+            .collect::<Vec<_>>();
+
+        for (id, overrides, mut applied) in instances {
+            prefab.apply(self, id, &overrides, &mut applied);
+            if let Some(mut link) = self.get_component_mut::<PrefabLink>(id) {
+                link.applied = applied;
+            }
+        }
+
+        let children = prefab.data.borrow().children.clone();
+        for child in children {
+            self.propagate_prefab(&child);
+        }
+    }
+}
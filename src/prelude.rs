@@ -1,7 +1,28 @@
 #![allow(unused)]
 pub use crate::app::*;
+pub use crate::bundle::*;
 pub use crate::component::*;
+pub use crate::dynamic::*;
 pub use crate::entity_builder::*;
+pub use crate::entity_map::*;
+pub use crate::event::*;
+pub use crate::hierarchy::*;
+pub use crate::hooks::*;
+pub use crate::observer::*;
+pub use crate::plugin::*;
+pub use crate::prefab::*;
+pub use crate::registry::*;
+pub use crate::relation::*;
+pub use crate::removed::*;
+pub use crate::runner::*;
+#[cfg(feature = "serde")]
+pub use crate::scene::*;
 pub use crate::scheduler::*;
+pub use crate::snapshot::*;
+pub use crate::sub_app::*;
+pub use crate::system_param::*;
+#[cfg(feature = "serde")]
+pub use crate::serialize::*;
+pub use crate::transform::*;
 pub use crate::world::*;
 pub use crate::*;
@@ -0,0 +1,332 @@
+use crate::{
+    component::{Component, ComponentStorage, EntityId},
+    world::World,
+};
+use std::{
+    any::{Any, TypeId},
+    cell::Ref,
+    collections::HashMap,
+    fmt,
+};
+
+type GetFn = for<'a> fn(&'a World, EntityId) -> Option<Ref<'a, dyn Any>>;
+type InsertFn = fn(&mut World, EntityId, Box<dyn Any>);
+type DefaultFn = fn() -> Box<dyn Any>;
+type CloneFn = fn(&dyn Any) -> Box<dyn Any>;
+type DebugFn = fn(&dyn Any, &mut fmt::Formatter<'_>) -> fmt::Result;
+#[cfg(feature = "serde")]
+pub(crate) type SerializeFn = fn(&dyn Any) -> &(dyn erased_serde::Serialize + 'static);
+#[cfg(feature = "serde")]
+pub(crate) type DeserializeFn =
+    fn(&mut dyn erased_serde::Deserializer) -> Result<Box<dyn Any>, erased_serde::Error>;
+
+/// Runtime metadata of a component type, looked up by its stable name through the `TypeRegistry`.
+///
+/// Values are passed around as `dyn Any`; every function panics if given a value of another type.
+#[derive(Clone)]
+pub struct ComponentInfo {
+    name: &'static str,
+    type_name: &'static str,
+    type_id: TypeId,
+    // keyed like `World::components`
+    pub(crate) storage_id: TypeId,
+    size: usize,
+    pub(crate) get: GetFn,
+    pub(crate) insert: InsertFn,
+    default: Option<DefaultFn>,
+    clone: Option<CloneFn>,
+    debug: Option<DebugFn>,
+    #[cfg(feature = "serde")]
+    pub(crate) serialize: Option<SerializeFn>,
+    #[cfg(feature = "serde")]
+    pub(crate) deserialize: Option<DeserializeFn>,
+}
+
+impl ComponentInfo {
+    fn new<T>() -> Self
+    where
+        T: Component + 'static,
+    {
+        Self {
+            name: std::any::type_name::<T>(),
+            type_name: std::any::type_name::<T>(),
+            type_id: TypeId::of::<T>(),
+            storage_id: TypeId::of::<ComponentStorage<T>>(),
+            size: std::mem::size_of::<T>(),
+            get: |world, key| {
+                let component = world.get_component::<T>(key)?;
+
+                Some(Ref::map(component, |inner| inner as &dyn Any))
+            },
+            insert: |world, key, component| {
+                world.add_component(key, *component.downcast::<T>().unwrap());
+            },
+            default: None,
+            clone: None,
+            debug: None,
+            #[cfg(feature = "serde")]
+            serialize: None,
+            #[cfg(feature = "serde")]
+            deserialize: None,
+        }
+    }
+
+    /// The stable name of the component. Defaults to the Rust type name until set with `ComponentRegistration::named`.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The Rust type name of the component. Not guaranteed to be stable across compiler versions.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// The size of the component in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Creates a default value of the component, if registered with `ComponentRegistration::reflect_default`.
+    pub fn default_value(&self) -> Option<Box<dyn Any>> {
+        self.default.map(|default| default())
+    }
+
+    /// Clones a value of the component, if registered with `World::register_cloneable`.
+    pub fn clone_value(&self, value: &dyn Any) -> Option<Box<dyn Any>> {
+        self.clone.map(|clone| clone(value))
+    }
+
+    /// Formats a value of the component with `Debug`, if registered with `ComponentRegistration::reflect_debug`.
+    pub fn debug_value(&self, value: &dyn Any) -> Option<String> {
+        struct Debugged<'a>(&'a dyn Any, DebugFn);
+
+        impl<'a> fmt::Debug for Debugged<'a> {
+            fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                (self.1)(self.0, formatter)
+            }
+        }
+
+        self.debug
+            .map(|debug| format!("{:?}", Debugged(value, debug)))
+    }
+
+    /// Gets a value of the component as something serde can serialize, if registered with `ComponentRegistration::serializable`.
+    #[cfg(feature = "serde")]
+    pub fn serialize_value<'a>(
+        &self,
+        value: &'a dyn Any,
+    ) -> Option<&'a (dyn erased_serde::Serialize + 'static)> {
+        self.serialize.map(|serialize| serialize(value))
+    }
+
+    /// Reads a value of the component, if registered with `ComponentRegistration::serializable`.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_value(
+        &self,
+        deserializer: &mut dyn erased_serde::Deserializer,
+    ) -> Option<Result<Box<dyn Any>, erased_serde::Error>> {
+        self.deserialize
+            .map(|deserialize| deserialize(deserializer))
+    }
+}
+
+/// Maps stable component names to their `ComponentInfo`. Owned by `World`; see `World::type_registry`.
+#[derive(Default)]
+pub struct TypeRegistry {
+    // in registration order
+    infos: Vec<ComponentInfo>,
+    by_name: HashMap<&'static str, usize>,
+    by_type: HashMap<TypeId, usize>,
+}
+
+impl TypeRegistry {
+    /// Gets a component's metadata by its stable name.
+    pub fn get(&self, name: &str) -> Option<&ComponentInfo> {
+        self.by_name.get(name).map(|&index| &self.infos[index])
+    }
+
+    /// Gets a component's metadata by its type.
+    pub fn get_by_type_id(&self, id: TypeId) -> Option<&ComponentInfo> {
+        self.by_type.get(&id).map(|&index| &self.infos[index])
+    }
+
+    /// Gets the metadata of `T`.
+    pub fn get_of<T>(&self) -> Option<&ComponentInfo>
+    where
+        T: Component + 'static,
+    {
+        self.get_by_type_id(TypeId::of::<T>())
+    }
+
+    /// Iterates over all registered components in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &ComponentInfo> {
+        self.infos.iter()
+    }
+
+    /// Gets the metadata of `T`, adding it under its type name if it is missing.
+    pub(crate) fn info_mut<T>(&mut self) -> &mut ComponentInfo
+    where
+        T: Component + 'static,
+    {
+        let id = TypeId::of::<T>();
+        let index = match self.by_type.get(&id) {
+            Some(&index) => index,
+            None => {
+                let info = ComponentInfo::new::<T>();
+                self.by_name.insert(info.name, self.infos.len());
+                self.by_type.insert(id, self.infos.len());
+                self.infos.push(info);
+
+                self.infos.len() - 1
+            }
+        };
+
+        &mut self.infos[index]
+    }
+
+    /// Adds metadata copied from another world's registry.
+    /// # Panics
+    /// Panics if another component already uses the same name.
+    pub(crate) fn add_info(&mut self, info: ComponentInfo) {
+        if let Some(other) = self.get(info.name) {
+            assert!(
+                other.type_id == info.type_id,
+                "Component name `{}` is already used by {}",
+                info.name,
+                other.type_name
+            );
+        }
+        self.remove(info.type_id);
+
+        self.by_name.insert(info.name, self.infos.len());
+        self.by_type.insert(info.type_id, self.infos.len());
+        self.infos.push(info);
+    }
+
+    /// Sets the stable name of `T`.
+    /// # Panics
+    /// Panics if another component already uses `name`.
+    pub(crate) fn set_name<T>(&mut self, name: &'static str)
+    where
+        T: Component + 'static,
+    {
+        let old_name = self.info_mut::<T>().name;
+        let index = self.by_type[&TypeId::of::<T>()];
+
+        if let Some(&other) = self.by_name.get(name) {
+            assert!(
+                other == index,
+                "Component name `{name}` is already used by {}",
+                self.infos[other].type_name
+            );
+        }
+        self.by_name.remove(old_name);
+        self.by_name.insert(name, index);
+        self.infos[index].name = name;
+    }
+
+    /// Removes the metadata of a component type.
+    pub(crate) fn remove(&mut self, id: TypeId) {
+        let Some(index) = self.by_type.remove(&id) else {
+            return;
+        };
+
+        self.infos.remove(index);
+        self.by_name.clear();
+        self.by_type.clear();
+        for (index, info) in self.infos.iter().enumerate() {
+            self.by_name.insert(info.name, index);
+            self.by_type.insert(info.type_id, index);
+        }
+    }
+}
+
+/// Reflection configuration, set through `ComponentRegistration`.
+impl TypeRegistry {
+    pub(crate) fn set_default<T>(&mut self)
+    where
+        T: Component + Default + 'static,
+    {
+        self.info_mut::<T>().default = Some(|| Box::new(T::default()));
+    }
+
+    pub(crate) fn set_clone<T>(&mut self)
+    where
+        T: Component + Clone + 'static,
+    {
+        self.info_mut::<T>().clone =
+            Some(|value| Box::new(value.downcast_ref::<T>().unwrap().clone()));
+    }
+
+    pub(crate) fn set_debug<T>(&mut self)
+    where
+        T: Component + fmt::Debug + 'static,
+    {
+        self.info_mut::<T>().debug =
+            Some(|value, formatter| fmt::Debug::fmt(value.downcast_ref::<T>().unwrap(), formatter));
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn set_serde<T>(&mut self)
+    where
+        T: Component + serde::Serialize + serde::de::DeserializeOwned + 'static,
+    {
+        let info = self.info_mut::<T>();
+
+        info.serialize =
+            Some(|value| value.downcast_ref::<T>().unwrap() as &dyn erased_serde::Serialize);
+        info.deserialize =
+            Some(|deserializer| Ok(Box::new(erased_serde::deserialize::<T>(deserializer)?)));
+    }
+}
+
+/// Name-based component access, for tools, scene files and scripting.
+impl World {
+    /// Gets the registry of named component types.
+    pub fn type_registry(&self) -> &TypeRegistry {
+        &self.registry
+    }
+
+    /// Gets a component of an entity by the component's stable name.
+    pub fn get_component_by_name(&self, key: EntityId, name: &str) -> Option<Ref<'_, dyn Any>> {
+        (self.registry.get(name)?.get)(self, key)
+    }
+
+    /// Adds a component to an entity by the component's stable name. Returns `false` if no component uses `name`.
+    /// # Panics
+    /// Panics if `value` is not of the named component's type.
+    pub fn add_component_by_name(
+        &mut self,
+        key: EntityId,
+        name: &str,
+        value: Box<dyn Any>,
+    ) -> bool {
+        let Some(insert) = self.registry.get(name).map(|info| info.insert) else {
+            return false;
+        };
+        insert(self, key, value);
+
+        true
+    }
+
+    /// Gets the stable names of all named components of an entity.
+    pub fn component_names(&self, key: EntityId) -> Vec<&'static str> {
+        self.registry
+            .iter()
+            .filter(|info| (info.get)(self, key).is_some())
+            .map(|info| info.name)
+            .collect()
+    }
+
+    /// Formats a component of an entity with `Debug`, if the component was registered with `ComponentRegistration::reflect_debug`.
+    pub fn debug_component(&self, key: EntityId, name: &str) -> Option<String> {
+        let info = self.registry.get(name)?;
+        let value = (info.get)(self, key)?;
+
+        info.debug_value(&*value)
+    }
+}
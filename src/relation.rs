@@ -0,0 +1,295 @@
+use crate::{
+    component::{Component, EntityId},
+    entity_map::{EntityMap, MapEntities},
+    world::World,
+};
+use std::{cell::RefMut, collections::HashMap};
+
+/// Holds every `(R, target)` pair of an entity for the relation type `R`, in insertion order.
+/// This component is managed by `World`; use `World::add_relation` and `World::remove_relation` to change it.
+///
+/// Querying for `Relations<R>` matches every entity that has an `R` relationship to anything.
+pub struct Relations<R>
+where
+    R: 'static,
+{
+    pairs: Vec<(EntityId, R)>,
+}
+impl<R> Component for Relations<R> where R: 'static {}
+
+/// Relationships to entities missing from `map` are dropped, like parent-child links.
+impl<R> MapEntities for Relations<R>
+where
+    R: 'static,
+{
+    fn map_entities(&mut self, map: &EntityMap) {
+        self.pairs.retain_mut(|(target, _)| match map.get(*target) {
+            Some(mapped) => {
+                *target = mapped;
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+impl<R> Relations<R>
+where
+    R: 'static,
+{
+    /// Gets the relation data pointing at `target` if it exists.
+    pub fn get(&self, target: EntityId) -> Option<&R> {
+        self.pairs
+            .iter()
+            .find(|(id, _)| *id == target)
+            .map(|(_, relation)| relation)
+    }
+
+    /// Checks if there is a relationship pointing at `target`.
+    pub fn contains(&self, target: EntityId) -> bool {
+        self.get(target).is_some()
+    }
+
+    /// Iterates over all targets.
+    pub fn targets(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.pairs.iter().map(|(id, _)| *id)
+    }
+
+    /// Iterates over all `(target, relation)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &R)> {
+        self.pairs.iter().map(|(id, relation)| (*id, relation))
+    }
+}
+
+/// The target side of a relationship query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// Matches any target.
+    Any,
+    /// Matches only the given entity.
+    Entity(EntityId),
+}
+
+impl Target {
+    fn matches(&self, key: EntityId) -> bool {
+        match self {
+            Target::Any => true,
+            Target::Entity(target) => *target == key,
+        }
+    }
+}
+
+/// Reverse index of the relation type `R`, from each target to the entities that have an `R` relationship to it.
+/// Added as a resource by `World::register_relation` and kept up to date by the `Relations<R>` hooks.
+pub(crate) struct RelationSources<R>
+where
+    R: 'static,
+{
+    sources: HashMap<EntityId, Vec<EntityId>>,
+    marker: std::marker::PhantomData<R>,
+}
+impl<R> Component for RelationSources<R> where R: 'static {}
+
+impl<R> RelationSources<R>
+where
+    R: 'static,
+{
+    fn index(&mut self, source: EntityId, target: EntityId) {
+        let sources = self.sources.entry(target).or_default();
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+
+    fn unindex(&mut self, source: EntityId, target: EntityId) {
+        if let Some(sources) = self.sources.get_mut(&target) {
+            sources.retain(|id| *id != source);
+            if sources.is_empty() {
+                self.sources.remove(&target);
+            }
+        }
+    }
+}
+
+/// Gets the index of `R`, adding it if `R` was registered by moving entities into the world.
+fn relation_sources<R>(world: &mut World) -> RefMut<'_, RelationSources<R>>
+where
+    R: 'static,
+{
+    if world.get_resource::<RelationSources<R>>().is_none() {
+        world.add_resource(RelationSources::<R> {
+            sources: HashMap::new(),
+            marker: std::marker::PhantomData,
+        });
+    }
+
+    world.get_resource_mut::<RelationSources<R>>().unwrap()
+}
+
+/// Indexes every target of `source`. Set as the `on_insert` hook of `Relations<R>`.
+fn index_relations<R>(world: &mut World, source: EntityId)
+where
+    R: 'static,
+{
+    let targets = world.targets::<R>(source);
+    let mut index = relation_sources::<R>(world);
+    for target in targets {
+        index.index(source, target);
+    }
+}
+
+/// Removes every target of `source` from the index. Set as the `on_remove` hook of `Relations<R>`.
+fn unindex_relations<R>(world: &mut World, source: EntityId)
+where
+    R: 'static,
+{
+    let targets = world.targets::<R>(source);
+    if let Some(mut index) = world.get_resource_mut::<RelationSources<R>>() {
+        for target in targets {
+            index.unindex(source, target);
+        }
+    }
+}
+
+/// Removes every `R` relationship pointing at `target`. Registered as a cleanup by `World::register_relation`.
+pub(crate) fn remove_relations_to<R>(world: &mut World, target: EntityId)
+where
+    R: 'static,
+{
+    let sources = world
+        .get_resource_mut::<RelationSources<R>>()
+        .and_then(|mut index| index.sources.remove(&target))
+        .unwrap_or_default();
+    for source in sources {
+        world.remove_relation::<R>(source, target);
+    }
+}
+
+/// Relationship operations. A relationship is a pair of a relation type `R` and a target entity; an entity may have many targets for the same relation.
+impl World {
+    /// Registers the relation type `R` with the world, unless it is already registered.
+    /// Relationships pointing at an entity are removed when that entity is removed.
+    ///
+    /// The world keeps an index from each target to its sources, so `World::sources` and the cleanup on removal
+    /// don't need to scan every entity.
+    pub fn register_relation<R>(&mut self)
+    where
+        R: 'static,
+    {
+        if self.contains_storage::<Relations<R>>() {
+            return;
+        }
+
+        relation_sources::<R>(self);
+        self.register::<Relations<R>>()
+            .on_insert(index_relations::<R>)
+            .on_remove(unindex_relations::<R>)
+            .map_entities();
+        self.add_storage_cleanup::<Relations<R>>(remove_relations_to::<R>);
+    }
+
+    /// Adds the relationship `(relation, target)` to `source`, replacing the previous data if the pair already exists.
+    /// Registers `R` first if needed.
+    pub fn add_relation<R>(&mut self, source: EntityId, target: EntityId, relation: R) -> Option<R>
+    where
+        R: 'static,
+    {
+        self.register_relation::<R>();
+        if let Some(mut relations) = self.get_component_mut::<Relations<R>>(source) {
+            if let Some((_, previous)) = relations.pairs.iter_mut().find(|(id, _)| *id == target) {
+                return Some(std::mem::replace(previous, relation));
+            }
+
+            relations.pairs.push((target, relation));
+            drop(relations);
+            if let Some(mut index) = self.get_resource_mut::<RelationSources<R>>() {
+                index.index(source, target);
+            }
+
+            return None;
+        }
+
+        self.add_component(
+            source,
+            Relations {
+                pairs: vec![(target, relation)],
+            },
+        );
+
+        None
+    }
+
+    /// Removes the relationship `(R, target)` from `source`.
+    pub fn remove_relation<R>(&mut self, source: EntityId, target: EntityId) -> Option<R>
+    where
+        R: 'static,
+    {
+        let (removed, now_empty) = {
+            let mut relations = self.get_component_mut::<Relations<R>>(source)?;
+            let index = relations.pairs.iter().position(|(id, _)| *id == target)?;
+            let (_, removed) = relations.pairs.remove(index);
+
+            (removed, relations.pairs.is_empty())
+        };
+        if let Some(mut index) = self.get_resource_mut::<RelationSources<R>>() {
+            index.unindex(source, target);
+        }
+        if now_empty {
+            self.remove_component::<Relations<R>>(source);
+        }
+
+        Some(removed)
+    }
+
+    /// Checks if `source` has the relationship `(R, target)`.
+    pub fn has_relation<R>(&self, source: EntityId, target: EntityId) -> bool
+    where
+        R: 'static,
+    {
+        self.get_component::<Relations<R>>(source)
+            .map(|relations| relations.contains(target))
+            .unwrap_or(false)
+    }
+
+    /// Gets every target of `source` for the relation `R`.
+    pub fn targets<R>(&self, source: EntityId) -> Vec<EntityId>
+    where
+        R: 'static,
+    {
+        self.get_component::<Relations<R>>(source)
+            .map(|relations| relations.targets().collect())
+            .unwrap_or_default()
+    }
+
+    /// Gets every entity that has the relationship `(R, target)`.
+    pub fn sources<R>(&self, target: EntityId) -> Vec<EntityId>
+    where
+        R: 'static,
+    {
+        self.get_resource::<RelationSources<R>>()
+            .and_then(|index| index.sources.get(&target).cloned())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|source| self.has_relation::<R>(*source, target))
+            .collect()
+    }
+
+    /// Iterates over all `(source, target)` pairs of the relation `R` whose target matches `target`.
+    /// `Target::Any` acts as a wildcard.
+    pub fn query_relation<R>(
+        &self,
+        target: Target,
+    ) -> impl Iterator<Item = (EntityId, EntityId)> + '_
+    where
+        R: 'static,
+    {
+        self.query::<(EntityId, Relations<R>)>()
+            .flat_map(move |(source, relations)| {
+                relations
+                    .targets()
+                    .filter(|id| target.matches(*id))
+                    .map(|id| (*source, id))
+                    .collect::<Vec<_>>()
+            })
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    component::{Component, ComponentStorage, EntityId},
+    event::EventReader,
+    world::World,
+};
+use std::{any::TypeId, marker::PhantomData};
+
+/// Reads the entities that lost a component `T`, either through `World::remove_component` or because the entity was removed.
+/// Removals are only recorded for component types passed to `World::track_removals`.
+///
+/// Removals are double buffered like `Events`: they stay readable for the frame they happened in and the frame after,
+/// and `App::run` swaps the buffers at the end of every frame. Each reader has its own cursor, so keep it in a resource
+/// or component to see every removal exactly once.
+pub struct RemovedComponents<T>
+where
+    T: Component + 'static,
+{
+    reader: EventReader<EntityId>,
+    marker: PhantomData<fn() -> T>,
+}
+impl<T> Component for RemovedComponents<T> where T: Component + 'static {}
+
+impl<T> Default for RemovedComponents<T>
+where
+    T: Component + 'static,
+{
+    fn default() -> Self {
+        Self {
+            reader: EventReader::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> RemovedComponents<T>
+where
+    T: Component + 'static,
+{
+    /// Iterates over the entities that lost a `T` since the last read, oldest first.
+    pub fn read<'a>(&'a mut self, world: &'a World) -> impl Iterator<Item = EntityId> + 'a {
+        world
+            .removed
+            .get(&TypeId::of::<ComponentStorage<T>>())
+            .into_iter()
+            .flat_map(|events| self.reader.read(events).copied())
+    }
+}
+
+/// Removal tracking operations.
+impl World {
+    /// Starts recording the removals of `T`, so they can be read with `RemovedComponents<T>`. Tracking the same type twice does nothing.
+    ///
+    /// The recorded removals are dropped by `World::update_removed`, which `App` calls every frame.
+    /// When using a `World` on its own, call it once per frame as well, or the removals pile up.
+    pub fn track_removals<T>(&mut self)
+    where
+        T: Component + 'static,
+    {
+        self.removed
+            .entry(TypeId::of::<ComponentStorage<T>>())
+            .or_default();
+    }
+
+    /// Checks if the removals of the component stored under `id` are recorded.
+    pub(crate) fn tracks_removals(&self, id: TypeId) -> bool {
+        self.removed.contains_key(&id)
+    }
+
+    /// Records that `key` lost the component stored under `id`, if its removals are tracked.
+    pub(crate) fn record_removal(&mut self, id: TypeId, key: EntityId) {
+        if let Some(events) = self.removed.get_mut(&id) {
+            events.send(key);
+        }
+    }
+
+    /// Swaps the removal buffers of every tracked component type, dropping removals recorded two updates ago.
+    /// Called once per frame by `App::run`.
+    pub fn update_removed(&mut self) {
+        for events in self.removed.values_mut() {
+            events.update();
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::app::App;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Drives an `App` once `App::run` is called, e.g. by looping over `App::update`. Set with `App::set_runner`.
+pub type Runner = Box<dyn FnOnce(&mut App)>;
+
+/// Runs frames until `World::shutdown` is called. The default runner.
+pub fn run_loop(app: &mut App) {
+    app.startup();
+
+    while app.world().active() {
+        app.update();
+    }
+}
+
+/// Runs a single frame, then shuts the app down.
+pub fn run_once(app: &mut App) {
+    app.update();
+    finish(app);
+}
+
+/// Creates a runner that runs `frames` frames, stopping early if `World::shutdown` is called.
+/// The app is shut down once the frames are used up.
+pub fn run_frames(frames: usize) -> impl FnOnce(&mut App) {
+    move |app| {
+        app.startup();
+
+        for _ in 0..frames {
+            if !app.world().active() {
+                break;
+            }
+            app.update();
+        }
+        finish(app);
+    }
+}
+
+/// Requests an exit and runs the shutdown systems for a runner that stops on its own, unless the app already exited.
+/// The runner stops even if a shutdown system vetoes the exit.
+fn finish(app: &mut App) {
+    if app.world().active() {
+        app.world_mut().shutdown();
+        app.run_shutdown();
+    }
+}
+
+/// Creates a runner that runs frames until `World::shutdown` is called, starting one every `period`.
+/// It sleeps between frames that finish early, and starts the next frame right away after frames that run late.
+pub fn run_fixed_rate(period: Duration) -> impl FnOnce(&mut App) {
+    move |app| {
+        app.startup();
+
+        let mut next = Instant::now();
+        while app.world().active() {
+            app.update();
+
+            next += period;
+            let now = Instant::now();
+            if next > now {
+                thread::sleep(next - now);
+            } else {
+                // don't try to catch up on missed frames
+                next = now;
+            }
+        }
+    }
+}
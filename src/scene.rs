@@ -0,0 +1,317 @@
+use crate::{
+    component::EntityId,
+    entity_map::EntityMap,
+    registry::TypeRegistry,
+    serialize::{ComponentsSeed, ComponentsSer, Field, LoadedComponents},
+    world::World,
+};
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeSeq, SerializeStruct},
+    Deserializer, Serialize, Serializer,
+};
+use std::{any::TypeId, fmt};
+
+/// An entity read from a scene, before it is spawned.
+struct SceneEntity {
+    id: Option<EntityId>,
+    components: LoadedComponents,
+    children: Vec<SceneEntity>,
+}
+
+struct SceneSer<'a> {
+    world: &'a World,
+    roots: Vec<EntityId>,
+}
+
+impl<'a> Serialize for SceneSer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Scene", 1)?;
+        state.serialize_field(
+            "entities",
+            &EntitiesSer {
+                world: self.world,
+                keys: &self.roots,
+            },
+        )?;
+        state.end()
+    }
+}
+
+struct EntitiesSer<'a> {
+    world: &'a World,
+    keys: &'a [EntityId],
+}
+
+impl<'a> Serialize for EntitiesSer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.keys.len()))?;
+        for &key in self.keys {
+            seq.serialize_element(&EntitySer {
+                world: self.world,
+                key,
+            })?;
+        }
+        seq.end()
+    }
+}
+
+struct EntitySer<'a> {
+    world: &'a World,
+    key: EntityId,
+}
+
+impl<'a> Serialize for EntitySer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let children = self.world.children(self.key);
+
+        let mut state = serializer.serialize_struct("Entity", 3)?;
+        state.serialize_field("id", &self.key)?;
+        state.serialize_field(
+            "components",
+            &ComponentsSer {
+                world: self.world,
+                key: self.key,
+                hierarchy: false,
+            },
+        )?;
+        state.serialize_field(
+            "children",
+            &EntitiesSer {
+                world: self.world,
+                keys: &children,
+            },
+        )?;
+        state.end()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct SceneSeed<'a>(&'a TypeRegistry);
+
+impl<'a, 'de> DeserializeSeed<'de> for SceneSeed<'a> {
+    type Value = Vec<SceneEntity>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Scene", &["entities"], self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for SceneSeed<'a> {
+    type Value = Vec<SceneEntity>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a scene")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entities = Vec::new();
+        while let Some(Field(key)) = map.next_key::<Field>()? {
+            match key.as_str() {
+                "entities" => entities = map.next_value_seed(EntitiesSeed(self.0))?,
+                other => return Err(de::Error::unknown_field(other, &["entities"])),
+            }
+        }
+
+        Ok(entities)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct EntitiesSeed<'a>(&'a TypeRegistry);
+
+impl<'a, 'de> DeserializeSeed<'de> for EntitiesSeed<'a> {
+    type Value = Vec<SceneEntity>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for EntitiesSeed<'a> {
+    type Value = Vec<SceneEntity>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of entities")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut entities = Vec::new();
+        while let Some(entity) = seq.next_element_seed(EntitySeed(self.0))? {
+            entities.push(entity);
+        }
+
+        Ok(entities)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct EntitySeed<'a>(&'a TypeRegistry);
+
+impl<'a, 'de> DeserializeSeed<'de> for EntitySeed<'a> {
+    type Value = SceneEntity;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Entity", &["id", "components", "children"], self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for EntitySeed<'a> {
+    type Value = SceneEntity;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an entity")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entity = SceneEntity {
+            id: None,
+            components: Vec::new(),
+            children: Vec::new(),
+        };
+        while let Some(Field(key)) = map.next_key::<Field>()? {
+            match key.as_str() {
+                "id" => entity.id = Some(map.next_value::<EntityId>()?),
+                "components" => entity.components = map.next_value_seed(ComponentsSeed(self.0))?,
+                "children" => entity.children = map.next_value_seed(EntitiesSeed(self.0))?,
+                other => {
+                    return Err(de::Error::unknown_field(
+                        other,
+                        &["id", "components", "children"],
+                    ))
+                }
+            }
+        }
+
+        Ok(entity)
+    }
+}
+
+/// Scene operations, available with the `serde` feature.
+///
+/// A scene is a human-editable RON file listing entities with their components by registered name,
+/// and their children nested inside them:
+/// ```ron
+/// (
+///     entities: [
+///         (
+///             components: { "Name": ("player") },
+///             children: [
+///                 (components: { "Name": ("sword") }),
+///             ],
+///         ),
+///     ],
+/// )
+/// ```
+/// Every field of an entity is optional. The `id` written by `World::save_scene` is only used to remap
+/// entity IDs stored in components registered with `ComponentRegistration::map_entities`.
+impl World {
+    /// Spawns the entities of a scene, returning the top-level ones.
+    /// Hooks, required components and observers of the loaded components run once the whole scene is spawned and remapped.
+    /// Nothing is spawned if the scene cannot be read, e.g. because it uses an unregistered component name.
+    pub fn spawn_scene(&mut self, scene: &str) -> Result<Vec<EntityId>, ron::error::SpannedError> {
+        let mut deserializer = ron::Deserializer::from_str(scene)?;
+        let entities = SceneSeed(&self.registry)
+            .deserialize(&mut deserializer)
+            .map_err(|error| deserializer.span_error(error))?;
+        deserializer
+            .end()
+            .map_err(|error| deserializer.span_error(error))?;
+
+        let mut map = EntityMap::new();
+        let mut spawned = Vec::new();
+        let mut added = Vec::new();
+        let roots = entities
+            .into_iter()
+            .map(|entity| self.spawn_scene_entity(entity, None, &mut map, &mut spawned, &mut added))
+            .collect::<Vec<_>>();
+
+        // remap before linking the hierarchy, since `Parent` and `Children` are remapped too
+        for (key, _) in spawned.iter() {
+            self.map_entities(*key, &map);
+        }
+        for (child, parent) in spawned {
+            if let Some(parent) = parent {
+                self.add_child(parent, child);
+            }
+        }
+        // hooks, requires and observers only see remapped components
+        for (key, storage_id) in added {
+            self.component_added(storage_id, key);
+        }
+
+        Ok(roots)
+    }
+
+    /// Spawns one scene entity and its children, recording each spawned entity with its parent,
+    /// and each inserted component for `World::component_added`.
+    fn spawn_scene_entity(
+        &mut self,
+        entity: SceneEntity,
+        parent: Option<EntityId>,
+        map: &mut EntityMap,
+        spawned: &mut Vec<(EntityId, Option<EntityId>)>,
+        added: &mut Vec<(EntityId, TypeId)>,
+    ) -> EntityId {
+        let key = self.new_entity().build();
+        if let Some(id) = entity.id {
+            map.insert(id, key);
+        }
+        self.insert_loaded(key, entity.components, added);
+
+        spawned.push((key, parent));
+        for child in entity.children {
+            self.spawn_scene_entity(child, Some(key), map, spawned, added);
+        }
+
+        key
+    }
+
+    /// Saves entities, along with all of their descendants, as a scene that can be loaded with `World::spawn_scene`.
+    ///
+    /// Only components registered with `ComponentRegistration::serializable` are saved.
+    /// Entities that are descendants of other given entities are only saved once, nested inside their ancestor.
+    pub fn save_scene(&self, entities: &[EntityId]) -> Result<String, ron::Error> {
+        let roots = entities
+            .iter()
+            .copied()
+            .filter(|&key| {
+                !self
+                    .ancestors(key)
+                    .any(|ancestor| entities.contains(&ancestor))
+            })
+            .collect();
+
+        ron::ser::to_string_pretty(
+            &SceneSer { world: self, roots },
+            ron::ser::PrettyConfig::default(),
+        )
+    }
+}
@@ -1,13 +1,19 @@
 #![allow(unused)]
-use crate::world::World;
+use crate::{system_param::SystemParam, world::World};
 
 pub type WorldFn = fn(&mut World);
 
+/// A system taking a `SystemParam` instead of the world, see `Scheduler::add_system_with`.
+pub type ParamFn<P> = for<'w> fn(<P as SystemParam>::Item<'w>);
+
+// boxed, since systems taking a `SystemParam` need to know how to fetch it
+type BoxedSystem = Box<dyn FnMut(&mut World)>;
+
 #[derive(Default)]
 pub struct Scheduler {
     startup_systems: Vec<WorldFn>,
-    // TODO: perf test to see if this is better than Fn trait
-    systems: Vec<WorldFn>,
+    shutdown_systems: Vec<WorldFn>,
+    systems: Vec<BoxedSystem>,
 }
 
 /// A scheduler used to manage and execute systems within the ECS.
@@ -23,7 +29,22 @@ impl Scheduler {
 
     /// Adds a system to the app. Order of insertion does matter; systems that are added first are run first.
     pub fn add_system(&mut self, system: WorldFn) {
-        self.systems.push(system);
+        self.systems.push(Box::new(system));
+    }
+
+    /// Adds a system taking the parameter `P`, which is fetched from the world every time the system runs.
+    /// Runs in insertion order along with the systems added with `Scheduler::add_system`.
+    pub fn add_system_with<P>(&mut self, system: ParamFn<P>)
+    where
+        P: SystemParam + 'static,
+    {
+        self.systems
+            .push(Box::new(move |world: &mut World| system(P::fetch(world))));
+    }
+
+    /// Adds a shutdown system to the app. Order of insertion does matter; systems that are added first are run first.
+    pub fn add_shutdown_system(&mut self, shutdown_system: WorldFn) {
+        self.shutdown_systems.push(shutdown_system);
     }
 
     /// Runs all startup systems.
@@ -33,6 +54,13 @@ impl Scheduler {
         }
     }
 
+    /// Runs all shutdown systems.
+    pub fn run_shutdown_systems(&mut self, world: &mut World) {
+        for system in self.shutdown_systems.iter_mut() {
+            system(world);
+        }
+    }
+
     /// Runs all systems.
     pub fn run_systems(&mut self, world: &mut World) {
         for system in self.systems.iter_mut() {
@@ -0,0 +1,571 @@
+use crate::{
+    component::{Component, EntityId},
+    entity_map::{EntityMap, MapEntities},
+    hierarchy::{Children, Parent},
+    registry::{DeserializeFn, TypeRegistry},
+    world::World,
+};
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    any::{Any, TypeId},
+    cell::Ref,
+    fmt,
+};
+
+type SerializeResourceFn = for<'a> fn(&'a World) -> Option<Ref<'a, dyn erased_serde::Serialize>>;
+type InsertResourceFn = fn(&mut World, Box<dyn Any>, &EntityMap);
+// components read for an entity with the ID of their storage, waiting to be inserted
+pub(crate) type LoadedComponents = Vec<(TypeId, Box<dyn Any>)>;
+// a saved entity ID with its components
+type LoadedEntity = (EntityId, LoadedComponents);
+
+#[derive(Clone, Copy)]
+pub(crate) struct SerdeResource {
+    name: &'static str,
+    serialize: SerializeResourceFn,
+    deserialize: DeserializeFn,
+    insert: InsertResourceFn,
+}
+
+/// The resources that take part in world serialization, in registration order.
+/// Components are serialized through the `TypeRegistry` instead.
+#[derive(Default)]
+pub(crate) struct SerdeRegistry {
+    resources: Vec<SerdeResource>,
+}
+
+impl SerdeRegistry {
+    pub(crate) fn add_resource<T>(&mut self, name: &'static str)
+    where
+        T: Component + Serialize + DeserializeOwned + 'static,
+    {
+        let entry = SerdeResource {
+            name,
+            serialize: |world| {
+                let resource = world.get_resource::<T>()?;
+
+                Some(Ref::map(resource, |inner| {
+                    inner as &dyn erased_serde::Serialize
+                }))
+            },
+            deserialize: |deserializer| Ok(Box::new(erased_serde::deserialize::<T>(deserializer)?)),
+            insert: |world, resource, _| {
+                world.add_resource(*resource.downcast::<T>().unwrap());
+            },
+        };
+
+        self.resources.retain(|other| other.name != name);
+        self.resources.push(entry);
+    }
+
+    /// Like `SerdeRegistry::add_resource`, but remaps the entity IDs inside the resource when it is loaded.
+    pub(crate) fn add_mapped_resource<T>(&mut self, name: &'static str)
+    where
+        T: Component + Serialize + DeserializeOwned + MapEntities + 'static,
+    {
+        self.add_resource::<T>(name);
+        if let Some(entry) = self.resources.last_mut() {
+            entry.insert = |world, resource, map| {
+                let mut resource = *resource.downcast::<T>().unwrap();
+                resource.map_entities(map);
+                world.add_resource(resource);
+            };
+        }
+    }
+}
+
+/// Serializes every entity with its serializable components, along with the serializable resources, to any serde format.
+/// Created with `World::serializer`.
+pub struct WorldSerializer<'a> {
+    world: &'a World,
+}
+
+impl<'a> Serialize for WorldSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("World", 2)?;
+        state.serialize_field("entities", &EntitiesSer(self.world))?;
+        state.serialize_field("resources", &ResourcesSer(self.world))?;
+        state.end()
+    }
+}
+
+struct ErasedSer<'a>(Ref<'a, dyn erased_serde::Serialize>);
+
+impl<'a> Serialize for ErasedSer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        erased_serde::serialize(&*self.0, serializer)
+    }
+}
+
+struct EntitiesSer<'a>(&'a World);
+
+impl<'a> Serialize for EntitiesSer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let ids = self.0.query::<EntityId>().map(|id| *id).collect::<Vec<_>>();
+
+        let mut seq = serializer.serialize_seq(Some(ids.len()))?;
+        for id in ids {
+            seq.serialize_element(&EntitySer(self.0, id))?;
+        }
+        seq.end()
+    }
+}
+
+struct EntitySer<'a>(&'a World, EntityId);
+
+impl<'a> Serialize for EntitySer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Entity", 2)?;
+        state.serialize_field("id", &self.1)?;
+        state.serialize_field(
+            "components",
+            &ComponentsSer {
+                world: self.0,
+                key: self.1,
+                hierarchy: true,
+            },
+        )?;
+        state.end()
+    }
+}
+
+/// The serializable components of an entity, as a map of component names to components.
+pub(crate) struct ComponentsSer<'a> {
+    pub(crate) world: &'a World,
+    pub(crate) key: EntityId,
+    // whether `Parent` and `Children` are included
+    pub(crate) hierarchy: bool,
+}
+
+impl<'a> Serialize for ComponentsSer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let ComponentsSer {
+            world,
+            key,
+            hierarchy,
+        } = *self;
+
+        let mut map = serializer.serialize_map(None)?;
+        for info in world.registry.iter() {
+            let Some(serialize) = info.serialize else {
+                continue;
+            };
+            if !hierarchy
+                && (info.type_id() == TypeId::of::<Parent>()
+                    || info.type_id() == TypeId::of::<Children>())
+            {
+                continue;
+            }
+            if let Some(component) = (info.get)(world, key) {
+                let component = Ref::map(component, serialize);
+                map.serialize_entry(info.name(), &ErasedSer(component))?;
+            }
+        }
+        map.end()
+    }
+}
+
+struct ResourcesSer<'a>(&'a World);
+
+impl<'a> Serialize for ResourcesSer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let world = self.0;
+
+        let mut map = serializer.serialize_map(None)?;
+        for entry in world.serde.resources.iter() {
+            if let Some(resource) = (entry.serialize)(world) {
+                map.serialize_entry(entry.name, &ErasedSer(resource))?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// A struct field name. Read as an identifier rather than a string, which formats like RON require.
+pub(crate) struct Field(pub(crate) String);
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FieldVisitor;
+
+        impl<'de> Visitor<'de> for FieldVisitor {
+            type Value = Field;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a field name")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Field(value.to_string()))
+            }
+        }
+
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+/// Everything read from a serialized world, before it is inserted.
+#[derive(Default)]
+struct Loaded {
+    entities: Vec<LoadedEntity>,
+    resources: Vec<(InsertResourceFn, Box<dyn Any>)>,
+}
+
+#[derive(Clone, Copy)]
+struct WorldSeed<'a> {
+    types: &'a TypeRegistry,
+    serde: &'a SerdeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for WorldSeed<'a> {
+    type Value = Loaded;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("World", &["entities", "resources"], self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for WorldSeed<'a> {
+    type Value = Loaded;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a serialized world")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        Ok(Loaded {
+            entities: seq
+                .next_element_seed(EntitiesSeed(self))?
+                .ok_or_else(|| de::Error::invalid_length(0, &self))?,
+            resources: seq
+                .next_element_seed(ResourcesSeed(self))?
+                .ok_or_else(|| de::Error::invalid_length(1, &self))?,
+        })
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut loaded = Loaded::default();
+        while let Some(Field(key)) = map.next_key::<Field>()? {
+            match key.as_str() {
+                "entities" => loaded.entities = map.next_value_seed(EntitiesSeed(self))?,
+                "resources" => loaded.resources = map.next_value_seed(ResourcesSeed(self))?,
+                other => return Err(de::Error::unknown_field(other, &["entities", "resources"])),
+            }
+        }
+
+        Ok(loaded)
+    }
+}
+
+struct EntitiesSeed<'a>(WorldSeed<'a>);
+
+impl<'a, 'de> DeserializeSeed<'de> for EntitiesSeed<'a> {
+    type Value = Vec<LoadedEntity>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for EntitiesSeed<'a> {
+    type Value = Vec<LoadedEntity>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of entities")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut entities = Vec::new();
+        while let Some(entity) = seq.next_element_seed(EntitySeed(self.0))? {
+            entities.push(entity);
+        }
+
+        Ok(entities)
+    }
+}
+
+struct EntitySeed<'a>(WorldSeed<'a>);
+
+impl<'a, 'de> DeserializeSeed<'de> for EntitySeed<'a> {
+    type Value = LoadedEntity;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("Entity", &["id", "components"], self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for EntitySeed<'a> {
+    type Value = LoadedEntity;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an entity")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let id = seq
+            .next_element::<EntityId>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let components = seq
+            .next_element_seed(ComponentsSeed(self.0.types))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok((id, components))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut id = None;
+        let mut components = None;
+        while let Some(Field(key)) = map.next_key::<Field>()? {
+            match key.as_str() {
+                "id" => id = Some(map.next_value::<EntityId>()?),
+                "components" => {
+                    components = Some(map.next_value_seed(ComponentsSeed(self.0.types))?)
+                }
+                other => return Err(de::Error::unknown_field(other, &["id", "components"])),
+            }
+        }
+
+        Ok((
+            id.ok_or_else(|| de::Error::missing_field("id"))?,
+            components.unwrap_or_default(),
+        ))
+    }
+}
+
+/// Reads a map of component names to components, looking the names up in the `TypeRegistry`.
+pub(crate) struct ComponentsSeed<'a>(pub(crate) &'a TypeRegistry);
+
+impl<'a, 'de> DeserializeSeed<'de> for ComponentsSeed<'a> {
+    type Value = LoadedComponents;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for ComponentsSeed<'a> {
+    type Value = LoadedComponents;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of component names to components")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut components = Vec::new();
+        while let Some(name) = map.next_key::<String>()? {
+            let info = self
+                .0
+                .get(&name)
+                .filter(|info| info.deserialize.is_some())
+                .ok_or_else(|| de::Error::custom(format!("unknown component `{name}`")))?;
+            let component = map.next_value_seed(ErasedSeed(info.deserialize.unwrap()))?;
+
+            components.push((info.storage_id, component));
+        }
+
+        Ok(components)
+    }
+}
+
+struct ResourcesSeed<'a>(WorldSeed<'a>);
+
+impl<'a, 'de> DeserializeSeed<'de> for ResourcesSeed<'a> {
+    type Value = Vec<(InsertResourceFn, Box<dyn Any>)>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for ResourcesSeed<'a> {
+    type Value = Vec<(InsertResourceFn, Box<dyn Any>)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of resource names to resources")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut resources = Vec::new();
+        while let Some(name) = map.next_key::<String>()? {
+            let entry = self
+                .0
+                .serde
+                .resources
+                .iter()
+                .find(|entry| entry.name == name)
+                .ok_or_else(|| de::Error::custom(format!("unknown resource `{name}`")))?;
+            let resource = map.next_value_seed(ErasedSeed(entry.deserialize))?;
+
+            resources.push((entry.insert, resource));
+        }
+
+        Ok(resources)
+    }
+}
+
+struct ErasedSeed(DeserializeFn);
+
+impl<'de> DeserializeSeed<'de> for ErasedSeed {
+    type Value = Box<dyn Any>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+
+        (self.0)(&mut deserializer).map_err(de::Error::custom)
+    }
+}
+
+/// Serialization operations, available with the `serde` feature.
+impl World {
+    /// Registers a resource to be saved by `World::serializer` and loaded by `World::deserialize` under `name`.
+    pub fn register_serializable_resource<T>(&mut self, name: &'static str)
+    where
+        T: Component + Serialize + DeserializeOwned + 'static,
+    {
+        self.serde.add_resource::<T>(name);
+    }
+
+    /// Like `World::register_serializable_resource`, but the entity IDs stored inside the resource are remapped
+    /// to the loaded entities by `World::deserialize`.
+    pub fn register_serializable_resource_with_entities<T>(&mut self, name: &'static str)
+    where
+        T: Component + Serialize + DeserializeOwned + MapEntities + 'static,
+    {
+        self.serde.add_mapped_resource::<T>(name);
+    }
+
+    /// Creates a serializable snapshot of every entity and its components registered with `ComponentRegistration::serializable`,
+    /// along with the resources registered with `World::register_serializable_resource`.
+    pub fn serializer(&self) -> WorldSerializer<'_> {
+        WorldSerializer { world: self }
+    }
+
+    /// Loads a world saved with `World::serializer`, adding its entities and replacing its resources.
+    ///
+    /// Loaded entities get new IDs; the returned `EntityMap` maps the saved IDs to them. Entity IDs stored inside components
+    /// registered with `ComponentRegistration::map_entities`, and inside resources registered with
+    /// `World::register_serializable_resource_with_entities`, are remapped the same way.
+    /// Hooks, required components and observers of the loaded components only run once every entity is remapped.
+    /// Nothing is inserted if deserialization fails.
+    pub fn deserialize<'de, D>(&mut self, deserializer: D) -> Result<EntityMap, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let loaded = WorldSeed {
+            types: &self.registry,
+            serde: &self.serde,
+        }
+        .deserialize(deserializer)?;
+
+        let mut map = EntityMap::new();
+        let entities = loaded
+            .entities
+            .into_iter()
+            .map(|(saved, components)| {
+                let id = self.new_entity().build();
+                map.insert(saved, id);
+
+                (id, components)
+            })
+            .collect::<Vec<_>>();
+
+        // hooks, requires and observers only run once the IDs inside the components are remapped
+        let mut added = Vec::new();
+        for (id, components) in entities {
+            self.insert_loaded(id, components, &mut added);
+        }
+        for (_, id) in map.iter() {
+            self.map_entities(id, &map);
+        }
+        for (id, storage_id) in added {
+            self.component_added(storage_id, id);
+        }
+        for (insert, resource) in loaded.resources {
+            insert(self, resource, &map);
+        }
+
+        Ok(map)
+    }
+
+    /// Inserts loaded components straight into their storages, recording each of them for `World::component_added`.
+    pub(crate) fn insert_loaded(
+        &mut self,
+        key: EntityId,
+        components: LoadedComponents,
+        added: &mut Vec<(EntityId, TypeId)>,
+    ) {
+        for (storage_id, component) in components {
+            if let Some(storage) = self.components.get_mut(&storage_id) {
+                storage.insert_entry(key, component);
+                added.push((key, storage_id));
+            }
+        }
+    }
+}
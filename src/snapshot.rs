@@ -0,0 +1,109 @@
+use crate::{
+    component::Component,
+    world::{EntityIds, World},
+};
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::HashMap,
+};
+
+/// A copy of a world's entities, cloneable components and cloneable resources, created by `World::snapshot`.
+pub struct Snapshot {
+    ids: EntityIds,
+    // storage snapshots, keyed like `World::components`
+    components: HashMap<TypeId, Box<dyn Any>>,
+    // keyed like `World::resources`
+    resources: HashMap<TypeId, Box<dyn Any>>,
+}
+
+/// Snapshot operations, e.g. for rollback netcode or undo.
+impl World {
+    /// Registers a resource type to be copied by `World::snapshot`.
+    pub fn register_cloneable_resource<T>(&mut self)
+    where
+        T: Component + Clone + 'static,
+    {
+        self.resource_cloners.insert(TypeId::of::<T>(), |resource| {
+            Box::new(resource.downcast_ref::<T>().unwrap().clone())
+        });
+    }
+
+    /// Copies every entity, every component registered with `World::register_cloneable`
+    /// and every resource registered with `World::register_cloneable_resource`.
+    pub fn snapshot(&self) -> Snapshot {
+        let components = self
+            .components
+            .iter()
+            .filter_map(|(id, storage)| Some((*id, storage.snapshot()?)))
+            .collect();
+        let resources = self
+            .resources
+            .iter()
+            .filter_map(|(id, resource)| {
+                let cloner = self.resource_cloners.get(id)?;
+
+                Some((*id, cloner(&**resource.borrow())))
+            })
+            .collect();
+
+        Snapshot {
+            ids: self.ids.clone(),
+            components,
+            resources,
+        }
+    }
+
+    /// Rewinds the world to a snapshot taken from it with `World::snapshot`. A snapshot can be restored any number of times.
+    ///
+    /// Entities and the entity allocator are restored exactly, so entities spawned afterwards get the same IDs as they did
+    /// after the snapshot was taken. Cloneable components and resources are replaced by their snapshotted values.
+    /// Other components are kept for entities that exist in the snapshot, and other resources are left untouched.
+    ///
+    /// No hooks or observers run for the restored components. Entities that did not exist in the snapshot are cleaned up as if
+    /// they were removed, dropping observers and relations targeting them. Components required by restored ones that are missing,
+    /// e.g. because they are not cloneable, are added afterwards like with `World::add_component`.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let gone = self
+            .ids
+            .keys()
+            .filter(|key| !snapshot.ids.contains_key(*key))
+            .collect::<Vec<_>>();
+
+        self.ids = snapshot.ids.clone();
+        for (id, storage) in self.components.iter_mut() {
+            if storage.is_cloneable() {
+                storage.restore(snapshot.components.get(id).map(|entries| &**entries));
+            } else {
+                storage.retain_entries(&|key| snapshot.ids.contains_key(key));
+            }
+        }
+        for (id, cloner) in self.resource_cloners.iter() {
+            match snapshot.resources.get(id) {
+                Some(resource) => {
+                    self.resources
+                        .insert(*id, RefCell::new(cloner(&**resource)));
+                }
+                None => {
+                    self.resources.remove(id);
+                }
+            }
+        }
+
+        for key in gone {
+            self.run_cleanups(key);
+        }
+
+        let required = self.required.keys().copied().collect::<Vec<_>>();
+        for id in required {
+            let keys = self
+                .ids
+                .keys()
+                .filter(|key| self.contains_entry(id, *key))
+                .collect::<Vec<_>>();
+            for key in keys {
+                self.add_required(id, key);
+            }
+        }
+    }
+}
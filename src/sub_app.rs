@@ -0,0 +1,131 @@
+use crate::{
+    app::{App, AppExit},
+    component::{Component, EntityId},
+    entity_map::EntityMap,
+    world::World,
+};
+
+/// Copies data from the main world into a sub-app's world. Runs every frame, right before the sub-app's frame.
+pub type ExtractFn = fn(&World, &mut World);
+
+/// An app owned by another app, with its own world and systems, e.g. for rendering or for a server running next to a client.
+pub struct SubApp {
+    app: App,
+    extract: ExtractFn,
+    // whether the shutdown systems ran, since the main app can keep updating after it exits
+    shut_down: bool,
+}
+
+/// Maps main world entities to the sub-app entities `extract_component` copied them to. A resource of the sub-app's world.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedEntities(pub EntityMap);
+
+impl Component for ExtractedEntities {}
+
+/// Copies every `T` of the main world onto matching entities of the sub-app's world. Entities are spawned in the sub-app's
+/// world the first time one of their components is extracted, and removed once they no longer exist in the main world.
+/// Registers `T` in the sub-app's world if needed.
+///
+/// Use it from an `ExtractFn`, e.g. `|main, sub| extract_component::<Position>(main, sub)`.
+pub fn extract_component<T>(main: &World, sub: &mut World)
+where
+    T: Component + Clone + 'static,
+{
+    sub.register_cloneable::<T>();
+    let mut extracted = sub
+        .get_resource_mut::<ExtractedEntities>()
+        .map(|mut extracted| std::mem::take(&mut extracted.0))
+        .unwrap_or_default();
+
+    // drop entities that are gone from the main world
+    let mut kept = EntityMap::new();
+    for (from, to) in extracted.iter() {
+        if main.contains_component::<EntityId>(from) {
+            kept.insert(from, to);
+        } else {
+            sub.remove_entity(to);
+        }
+    }
+    extracted = kept;
+
+    for (from, to) in extracted.iter() {
+        if !main.contains_component::<T>(from) {
+            sub.remove_component::<T>(to);
+        }
+    }
+    for (id, component) in main.query::<(EntityId, T)>() {
+        let to = match extracted.get(*id) {
+            Some(to) => to,
+            None => {
+                let to = sub.new_entity().build();
+                extracted.insert(*id, to);
+                to
+            }
+        };
+        sub.add_component(to, component.clone());
+    }
+
+    sub.add_resource(ExtractedEntities(extracted));
+}
+
+/// Sub-app operations.
+impl App {
+    /// Adds a sub-app under `label`. Every frame, after the main systems and commands, `extract` copies data from the main
+    /// world into the sub-app's world and the sub-app runs a frame with `App::update`.
+    /// Exits requested by the sub-app are cleared at the end of its frame; only the main world's exit ends `App::run`.
+    /// The sub-app's shutdown systems run once, after the main app's, when the main app shuts down without a veto.
+    /// # Panics
+    /// Panics if a sub-app with the same label was already added.
+    pub fn add_sub_app(&mut self, label: &'static str, app: App, extract: ExtractFn) -> &mut Self {
+        assert!(
+            self.sub_app(label).is_none(),
+            "Sub-app `{label}` was already added"
+        );
+        self.sub_apps.push((
+            label,
+            SubApp {
+                app,
+                extract,
+                shut_down: false,
+            },
+        ));
+
+        self
+    }
+
+    pub fn sub_app(&self, label: &str) -> Option<&App> {
+        self.sub_apps
+            .iter()
+            .find(|(other, _)| *other == label)
+            .map(|(_, sub_app)| &sub_app.app)
+    }
+
+    pub fn sub_app_mut(&mut self, label: &str) -> Option<&mut App> {
+        self.sub_apps
+            .iter_mut()
+            .find(|(other, _)| *other == label)
+            .map(|(_, sub_app)| &mut sub_app.app)
+    }
+
+    /// Extracts into and updates every sub-app, in the order they were added.
+    pub(crate) fn update_sub_apps(&mut self) {
+        let mut sub_apps = std::mem::take(&mut self.sub_apps);
+        for (_, sub_app) in sub_apps.iter_mut() {
+            (sub_app.extract)(self.world(), sub_app.app.world_mut());
+            sub_app.app.update_sub_app();
+        }
+        self.sub_apps = sub_apps;
+    }
+
+    /// Runs the shutdown systems of every sub-app that has not shut down yet, with the main app's `exit` requested
+    /// in their worlds.
+    pub(crate) fn shutdown_sub_apps(&mut self, exit: AppExit) {
+        for (_, sub_app) in self.sub_apps.iter_mut() {
+            if !sub_app.shut_down {
+                sub_app.shut_down = true;
+                sub_app.app.world_mut().exit(exit);
+                sub_app.app.run_shutdown();
+            }
+        }
+    }
+}
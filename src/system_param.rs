@@ -0,0 +1,124 @@
+use crate::{component::Component, query::Query, world::World};
+use std::{
+    cell::{Ref, RefMut},
+    marker::PhantomData,
+};
+
+/// Something a system added with `App::add_system_with` takes as its parameter, fetched from the world right before the
+/// system runs. Implemented for resources, queries and the world itself, and for structs of parameters with
+/// `#[derive(SystemParam)]`.
+pub trait SystemParam {
+    /// The parameter, borrowing from the world for `'w`.
+    type Item<'w>;
+
+    /// Fetches the parameter from the world.
+    fn fetch(world: &World) -> Self::Item<'_>;
+}
+
+/// Implements `SystemParam` for a struct whose fields are all system parameters. The struct can have at most one lifetime
+/// parameter, standing for the borrow of the world. Use `#[system_param(crate = path)]` if the `ecs` crate is not
+/// available as `::ecs`.
+pub use ecs_derive::SystemParam;
+
+impl SystemParam for &World {
+    type Item<'w> = &'w World;
+
+    fn fetch(world: &World) -> Self::Item<'_> {
+        world
+    }
+}
+
+/// A resource.
+/// # Panics
+/// Fetching panics if the resource does not exist; use `Option<Ref<T>>` for resources that may be missing.
+impl<'a, T> SystemParam for Ref<'a, T>
+where
+    T: Component + 'static,
+{
+    type Item<'w> = Ref<'w, T>;
+
+    fn fetch(world: &World) -> Self::Item<'_> {
+        world
+            .get_resource::<T>()
+            .unwrap_or_else(|| panic!("Resource {} does not exist", std::any::type_name::<T>()))
+    }
+}
+
+/// A mutable resource.
+/// # Panics
+/// Fetching panics if the resource does not exist; use `Option<RefMut<T>>` for resources that may be missing.
+impl<'a, T> SystemParam for RefMut<'a, T>
+where
+    T: Component + 'static,
+{
+    type Item<'w> = RefMut<'w, T>;
+
+    fn fetch(world: &World) -> Self::Item<'_> {
+        world
+            .get_resource_mut::<T>()
+            .unwrap_or_else(|| panic!("Resource {} does not exist", std::any::type_name::<T>()))
+    }
+}
+
+impl<'a, T> SystemParam for Option<Ref<'a, T>>
+where
+    T: Component + 'static,
+{
+    type Item<'w> = Option<Ref<'w, T>>;
+
+    fn fetch(world: &World) -> Self::Item<'_> {
+        world.get_resource::<T>()
+    }
+}
+
+impl<'a, T> SystemParam for Option<RefMut<'a, T>>
+where
+    T: Component + 'static,
+{
+    type Item<'w> = Option<RefMut<'w, T>>;
+
+    fn fetch(world: &World) -> Self::Item<'_> {
+        world.get_resource_mut::<T>()
+    }
+}
+
+/// A system parameter giving access to the entities matching `Q`, like `World::query` and `World::query_mut`.
+pub struct QueryParam<'w, Q> {
+    world: &'w World,
+    marker: PhantomData<Q>,
+}
+
+impl<'w, Q> QueryParam<'w, Q>
+where
+    Q: Query + 'static,
+{
+    pub fn iter(&self) -> impl Iterator<Item = Q::Output<'w>> {
+        self.world.query::<Q>()
+    }
+
+    pub fn iter_mut(&self) -> impl Iterator<Item = Q::OutputMut<'w>> {
+        self.world.query_mut::<Q>()
+    }
+
+    pub fn single(&self) -> Option<Q::Output<'w>> {
+        self.world.query_single::<Q>()
+    }
+
+    pub fn single_mut(&self) -> Option<Q::OutputMut<'w>> {
+        self.world.query_single_mut::<Q>()
+    }
+}
+
+impl<'a, Q> SystemParam for QueryParam<'a, Q>
+where
+    Q: Query + 'static,
+{
+    type Item<'w> = QueryParam<'w, Q>;
+
+    fn fetch(world: &World) -> Self::Item<'_> {
+        QueryParam {
+            world,
+            marker: PhantomData,
+        }
+    }
+}
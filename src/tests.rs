@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use crate::{component::Component, make_component, prelude::EntityId, world::World};
+    use crate::{
+        component::Component, hierarchy::Children, make_component, prelude::EntityId, world::World,
+    };
 
     #[test]
     fn basic_operations() {
@@ -290,4 +292,2143 @@ mod tests {
             assert!(ids.iter().position(|cell| **cell == e3).is_some());
         }
     }
+
+    #[test]
+    fn hierarchy() {
+        let mut world = World::new();
+
+        world.register::<i32>();
+
+        let root = world.new_entity().build();
+        let a = world.new_entity().with(1).build();
+        let b = world.new_entity().build();
+        let c = world.new_entity().build();
+
+        world.add_child(root, a);
+        world.add_child(root, b);
+        world.add_child(a, c);
+
+        assert_eq!(world.children(root), vec![a, b]);
+        assert_eq!(world.parent(c), Some(a));
+        assert_eq!(world.descendants(root).collect::<Vec<_>>(), vec![a, c, b]);
+        assert_eq!(world.ancestors(c).collect::<Vec<_>>(), vec![a, root]);
+
+        // reparenting keeps both sides consistent
+        world.set_parent(c, Some(b));
+        assert!(world.children(a).is_empty());
+        assert!(!world.contains_component::<Children>(a));
+        assert_eq!(world.children(b), vec![c]);
+
+        world.remove_child(root, b);
+        assert_eq!(world.parent(b), None);
+        assert_eq!(world.children(root), vec![a]);
+        world.add_child(root, b);
+
+        // removing an entity despawns its subtree and unlinks it from its parent
+        world.remove_entity(b);
+        assert_eq!(None, world.get_component::<EntityId>(c).as_deref());
+        assert_eq!(world.children(root), vec![a]);
+
+        world.remove_entity(root);
+        assert_eq!(None, world.get_component::<i32>(a).as_deref());
+        assert_eq!(world.query::<EntityId>().count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn hierarchy_cycle() {
+        let mut world = World::new();
+
+        let a = world.new_entity().build();
+        let b = world.new_entity().build();
+
+        world.add_child(a, b);
+        world.add_child(b, a);
+    }
+
+    #[test]
+    fn transform_propagation() {
+        use crate::transform::{propagate_transforms, GlobalTransform, Transform, Vec2};
+
+        let mut world = World::new();
+
+        let root = world
+            .new_entity()
+            .with(Transform::from_translation(Vec2::new(10.0, 0.0)))
+            .build();
+        let child = world
+            .new_entity()
+            .with(Transform {
+                translation: Vec2::new(1.0, 0.0),
+                rotation: std::f32::consts::FRAC_PI_2,
+                scale: Vec2::new(2.0, 2.0),
+            })
+            .build();
+        let grandchild = world
+            .new_entity()
+            .with(Transform::from_translation(Vec2::new(1.0, 0.0)))
+            .build();
+        world.add_child(root, child);
+        world.add_child(child, grandchild);
+
+        propagate_transforms(&mut world);
+
+        let translation = |world: &World, id| {
+            let t = world.get_component::<GlobalTransform>(id).unwrap().translation();
+            ((t.x * 1000.0).round() / 1000.0, (t.y * 1000.0).round() / 1000.0)
+        };
+        assert_eq!(translation(&world, child), (11.0, 0.0));
+        // rotated a quarter turn and scaled by 2 through the child
+        assert_eq!(translation(&world, grandchild), (11.0, 2.0));
+
+        world.get_component_mut::<Transform>(root).unwrap().translation = Vec2::ZERO;
+        propagate_transforms(&mut world);
+        assert_eq!(translation(&world, grandchild), (1.0, 2.0));
+
+        world.set_parent(grandchild, None);
+        propagate_transforms(&mut world);
+        assert_eq!(translation(&world, grandchild), (1.0, 0.0));
+    }
+
+    #[test]
+    fn transform_deep_hierarchy() {
+        use crate::transform::{propagate_transforms, Affine2, GlobalTransform, Transform, Vec2};
+
+        let mut world = World::new();
+
+        let root = world.new_entity().with(Transform::default()).build();
+        let mut leaf = root;
+        for _ in 0..1_000 {
+            let child = world
+                .new_entity()
+                .with(Transform::from_translation(Vec2::new(1.0, 0.0)))
+                .build();
+            world.add_child(leaf, child);
+            leaf = child;
+        }
+
+        let side = Transform::from_translation(Vec2::new(0.0, 1.0));
+        let side = world.new_entity().with(side).build();
+        world.add_child(root, side);
+
+        propagate_transforms(&mut world);
+        let x = world.get_component::<GlobalTransform>(leaf).unwrap().translation().x;
+        assert_eq!(x, 1_000.0);
+
+        // only the dirty subtree is recomputed, so a marker left in a clean sibling survives
+        let marker = Affine2::from_scale_angle_translation(Vec2::ONE, 0.0, Vec2::new(-7.0, -7.0));
+        world.get_component_mut::<GlobalTransform>(side).unwrap().affine = marker;
+        let middle = world.parent(leaf).unwrap();
+        world.get_component_mut::<Transform>(middle).unwrap().translation.x = 2.0;
+        propagate_transforms(&mut world);
+        let x = world.get_component::<GlobalTransform>(leaf).unwrap().translation().x;
+        assert_eq!(x, 1_001.0);
+        assert_eq!(world.get_component::<GlobalTransform>(side).unwrap().affine(), marker);
+
+        world.get_component_mut::<Transform>(root).unwrap().translation.x = 5.0;
+        propagate_transforms(&mut world);
+        let x = world.get_component::<GlobalTransform>(leaf).unwrap().translation().x;
+        assert_eq!(x, 1_006.0);
+        let side = world.get_component::<GlobalTransform>(side).unwrap().translation();
+        assert_eq!(side, Vec2::new(5.0, 1.0));
+    }
+
+    #[test]
+    fn relations() {
+        use crate::relation::{Relations, Target};
+
+        make_component! {
+            struct Likes;
+        }
+        make_component! {
+            #[derive(Debug, PartialEq)]
+            struct Owns(u32);
+        }
+
+        let mut world = World::new();
+
+        world.register_relation::<Likes>();
+        world.register_relation::<Owns>();
+
+        let alice = world.new_entity().build();
+        let bob = world.new_entity().build();
+        let sword = world.new_entity().build();
+        let shield = world.new_entity().build();
+
+        world.add_relation(alice, bob, Likes);
+        world.add_relation(bob, alice, Likes);
+        world.add_relation(bob, sword, Owns(1));
+        assert_eq!(world.add_relation(bob, sword, Owns(2)), Some(Owns(1)));
+        world.add_relation(bob, shield, Owns(1));
+
+        assert!(world.has_relation::<Likes>(alice, bob));
+        assert!(!world.has_relation::<Owns>(alice, sword));
+        assert_eq!(world.targets::<Owns>(bob), vec![sword, shield]);
+        assert_eq!(world.sources::<Likes>(bob), vec![alice]);
+        assert_eq!(world.query_relation::<Likes>(Target::Any).count(), 2);
+        assert_eq!(world.query::<Relations<Owns>>().count(), 1);
+        assert_eq!(
+            world
+                .get_component::<Relations<Owns>>(bob)
+                .unwrap()
+                .get(sword),
+            Some(&Owns(2))
+        );
+
+        // removing a target cleans up the relationships pointing at it
+        world.remove_entity(sword);
+        assert_eq!(world.targets::<Owns>(bob), vec![shield]);
+
+        assert_eq!(world.remove_relation::<Owns>(bob, shield), Some(Owns(1)));
+        assert_eq!(world.query::<Relations<Owns>>().count(), 0);
+
+        // the reverse index follows additions and removals on both sides
+        let carol = world.new_entity().build();
+        world.add_relation(carol, bob, Likes);
+        assert_eq!(world.sources::<Likes>(bob), vec![alice, carol]);
+        world.remove_relation::<Likes>(carol, bob);
+        assert_eq!(world.sources::<Likes>(bob), vec![alice]);
+        world.add_relation(carol, bob, Likes);
+        world.remove_entity(carol);
+        assert_eq!(world.sources::<Likes>(bob), vec![alice]);
+
+        world.remove_entity(alice);
+        assert_eq!(world.query_relation::<Likes>(Target::Any).count(), 0);
+        assert!(world.sources::<Likes>(bob).is_empty());
+    }
+
+    #[test]
+    fn clone_entity() {
+        use crate::observer::OnAdd;
+
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Name(String);
+        }
+        make_component! {
+            struct Handle;
+        }
+        make_component! {
+            #[derive(Default)]
+            struct Added(u32);
+        }
+
+        let mut world = World::new();
+
+        world.add_resource(Added::default());
+        world
+            .register_cloneable::<Name>()
+            .on_add(|world, _| world.get_resource_mut::<Added>().unwrap().0 += 1);
+        world.register::<Handle>();
+        world.observe::<OnAdd<Name>>(|world, _| {
+            world.get_resource_mut::<Added>().unwrap().0 += 10;
+        });
+
+        let parent = world.new_entity().build();
+        let original = world
+            .new_entity()
+            .with(Name("goblin".to_string()))
+            .with(Handle)
+            .build();
+        world.add_child(parent, original);
+
+        assert_eq!(
+            world.uncloneable_components(original),
+            vec![std::any::type_name::<Handle>()]
+        );
+
+        let copy = world.clone_entity(original);
+        assert_ne!(copy, original);
+        assert_eq!(*world.get_component::<EntityId>(copy).unwrap(), copy);
+        assert_eq!(
+            world.get_component::<Name>(copy).as_deref(),
+            Some(&Name("goblin".to_string()))
+        );
+        assert!(!world.contains_component::<Handle>(copy));
+        assert_eq!(world.children(parent), vec![original, copy]);
+        // hooks and observers ran for the original and for the copy
+        assert_eq!(world.get_resource::<Added>().unwrap().0, 22);
+
+        world.get_component_mut::<Name>(copy).unwrap().0.push('2');
+        assert_eq!(world.get_component::<Name>(original).unwrap().0, "goblin");
+    }
+
+    #[test]
+    fn prefabs() {
+        use crate::prefab::{Prefab, PrefabLink};
+
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Health(i32);
+        }
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Speed(i32);
+        }
+
+        let mut world = World::new();
+
+        world.register::<Health>();
+        world.register::<Speed>();
+
+        let weapon = Prefab::new().with(Speed(1));
+        let monster = Prefab::new()
+            .with(Health(100))
+            .with(Speed(10))
+            .with_child(weapon.clone());
+
+        let plain = world.instantiate(&monster).build();
+        let linked = world.instantiate_linked(&monster).build();
+        let fast = world.instantiate_linked(&monster).with(Speed(20)).build();
+
+        assert_eq!(world.get_component::<Health>(plain).as_deref(), Some(&Health(100)));
+        assert_eq!(world.get_component::<Speed>(fast).as_deref(), Some(&Speed(20)));
+        assert_eq!(world.children(linked).len(), 1);
+        assert!(!world.contains_component::<PrefabLink>(plain));
+
+        monster.set(Health(50));
+        monster.set(Speed(5));
+        weapon.set(Speed(2));
+        world.propagate_prefab(&monster);
+
+        assert_eq!(world.get_component::<Health>(plain).as_deref(), Some(&Health(100)));
+        assert_eq!(world.get_component::<Health>(linked).as_deref(), Some(&Health(50)));
+        assert_eq!(world.get_component::<Speed>(linked).as_deref(), Some(&Speed(5)));
+        assert_eq!(world.get_component::<Health>(fast).as_deref(), Some(&Health(50)));
+        assert_eq!(world.get_component::<Speed>(fast).as_deref(), Some(&Speed(20)));
+
+        let sword = world.children(linked)[0];
+        assert_eq!(world.get_component::<Speed>(sword).as_deref(), Some(&Speed(2)));
+
+        // runtime changes survive propagation unless the prefab value changed again
+        world.get_component_mut::<Health>(linked).unwrap().0 -= 10;
+        monster.set(Speed(6));
+        world.propagate_prefab(&monster);
+        assert_eq!(world.get_component::<Health>(linked).as_deref(), Some(&Health(40)));
+        assert_eq!(world.get_component::<Speed>(linked).as_deref(), Some(&Speed(6)));
+
+        // instantiating through commands
+        let mut commands = crate::commands::Commands::default();
+        commands.instantiate(&monster);
+        commands.instantiate_linked_with(&monster, |entity| {
+            entity.with(Speed(30));
+        });
+        commands.run_commands(&mut world);
+        assert_eq!(world.query::<Health>().count(), 5);
+        assert_eq!(world.query::<Speed>().filter(|speed| speed.0 == 30).count(), 1);
+
+        // instances don't add their ID twice
+        make_component! {
+            #[derive(Default)]
+            struct Inserted(usize);
+        }
+
+        world.add_resource(Inserted::default());
+        world
+            .register::<EntityId>()
+            .on_insert(|world, _| world.get_resource_mut::<Inserted>().unwrap().0 += 1);
+        world.instantiate(&weapon).build();
+        world.instantiate_linked(&weapon).build();
+        assert_eq!(world.get_resource::<Inserted>().unwrap().0, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Prefab cannot be a child of itself")]
+    fn prefab_cycle() {
+        use crate::prefab::Prefab;
+
+        let root = Prefab::new();
+        let child = Prefab::new().with_child(root.clone());
+        let _ = root.with_child(child);
+    }
+
+    #[test]
+    fn events() {
+        use crate::event::{EventReader, Events};
+
+        #[derive(Debug, PartialEq)]
+        struct Hit(i32);
+
+        let mut world = World::new();
+        world.add_event::<Hit>();
+
+        let mut early = EventReader::<Hit>::default();
+        let mut late = EventReader::<Hit>::default();
+
+        world.send_event(Hit(1));
+        world.event_writer::<Hit>().send_batch([Hit(2), Hit(3)]);
+        {
+            let events = world.get_resource::<Events<Hit>>().unwrap();
+            assert_eq!(early.len(&events), 3);
+            assert_eq!(
+                early.read(&events).collect::<Vec<_>>(),
+                vec![&Hit(1), &Hit(2), &Hit(3)]
+            );
+            assert!(early.is_empty(&events));
+        }
+
+        world.get_resource_mut::<Events<Hit>>().unwrap().update();
+        world.send_event(Hit(4));
+        {
+            let events = world.get_resource::<Events<Hit>>().unwrap();
+            assert_eq!(early.read(&events).collect::<Vec<_>>(), vec![&Hit(4)]);
+            // events from the previous frame are still visible to readers that missed them
+            assert_eq!(late.read(&events).count(), 4);
+        }
+
+        world.get_resource_mut::<Events<Hit>>().unwrap().update();
+        world.get_resource_mut::<Events<Hit>>().unwrap().update();
+        {
+            let events = world.get_resource::<Events<Hit>>().unwrap();
+            assert!(events.is_empty());
+            assert_eq!(events.event_count(), 4);
+            assert_eq!(EventReader::<Hit>::default().read(&events).count(), 0);
+        }
+    }
+
+    #[test]
+    fn app_events() {
+        use crate::{
+            app::App,
+            event::{EventReader, Events},
+        };
+
+        struct Tick;
+        struct Frame(u32);
+        impl Component for Frame {}
+        struct Seen(Vec<u32>);
+        impl Component for Seen {}
+
+        fn send(world: &mut World) {
+            let frame = world.get_resource::<Frame>().unwrap().0;
+            if frame.is_multiple_of(2) {
+                world.send_event(Tick);
+            }
+        }
+
+        fn receive(world: &mut World) {
+            let frame = {
+                let mut frame = world.get_resource_mut::<Frame>().unwrap();
+                frame.0 += 1;
+                frame.0
+            };
+            let count = {
+                let events = world.get_resource::<Events<Tick>>().unwrap();
+                let mut reader = world.get_resource_mut::<EventReader<Tick>>().unwrap();
+                reader.read(&events).count() as u32
+            };
+            world.get_resource_mut::<Seen>().unwrap().0.push(count);
+
+            if frame == 6 {
+                // every tick is seen exactly once, in the frame it was sent
+                let seen = world.get_resource::<Seen>().unwrap().0.clone();
+                assert_eq!(seen, vec![1, 0, 1, 0, 1, 0]);
+                world.shutdown();
+            }
+        }
+
+        fn setup(world: &mut World) {
+            world.add_resource(Frame(0));
+            world.add_resource(Seen(Vec::new()));
+            world.add_resource(EventReader::<Tick>::default());
+        }
+
+        let mut app = App::new();
+        app.add_event::<Tick>()
+            .add_event::<Tick>()
+            .add_startup_system(setup)
+            .add_system(send)
+            .add_system(receive);
+        app.run();
+
+        // events added to the world before the app still get updated every frame
+        let mut app = App::new();
+        app.world_mut().add_event::<Tick>();
+        app.add_event::<Tick>();
+        app.world_mut().send_event(Tick);
+        app.update();
+        app.update();
+        assert!(app.world().get_resource::<Events<Tick>>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn component_hooks() {
+        make_component! {
+            struct Body(i32);
+        }
+
+        // stands in for an external physics world
+        make_component! {
+            #[derive(Default)]
+            struct Log(Vec<String>);
+        }
+
+        fn log(world: &World, message: String) {
+            world.get_resource_mut::<Log>().unwrap().0.push(message);
+        }
+
+        let mut world = World::new();
+
+        world.add_resource(Log::default());
+        world
+            .register::<Body>()
+            .on_add(|world, key| {
+                let body = world.get_component::<Body>(key).unwrap().0;
+                log(world, format!("add {body}"));
+            })
+            .on_insert(|world, key| {
+                let body = world.get_component::<Body>(key).unwrap().0;
+                log(world, format!("insert {body}"));
+            })
+            .on_remove(|world, key| {
+                let body = world.get_component::<Body>(key).unwrap().0;
+                log(world, format!("remove {body}"));
+            });
+
+        let e1 = world.new_entity().with(Body(1)).build();
+        world.add_component(e1, Body(2));
+        world.remove_component::<Body>(e1);
+        world.remove_component::<Body>(e1);
+
+        let e2 = world.new_entity().with(Body(3)).build();
+        world.remove_entity(e2);
+
+        assert_eq!(
+            world.get_resource::<Log>().unwrap().0,
+            vec!["add 1", "insert 1", "insert 2", "remove 2", "add 3", "insert 3", "remove 3"]
+        );
+    }
+
+    #[test]
+    fn observers() {
+        use crate::{
+            commands::Commands,
+            observer::{OnAdd, OnRemove, Trigger},
+        };
+
+        make_component! {
+            #[derive(Debug, PartialEq)]
+            struct Health(i32);
+        }
+        make_component! {
+            #[derive(Debug, PartialEq, Default)]
+            struct Removed(u32);
+        }
+
+        struct Damage(i32);
+
+        let mut world = World::new();
+
+        world.register::<Health>();
+        world.add_resource(Removed::default());
+
+        world.observe::<OnAdd<Health>>(|world, trigger| {
+            let key = trigger.target().unwrap();
+            world.get_component_mut::<Health>(key).unwrap().0 += 1;
+        });
+        world.observe::<OnRemove<Health>>(|world, _| {
+            world.get_resource_mut::<Removed>().unwrap().0 += 1;
+        });
+
+        let root = world.new_entity().with(Health(100)).build();
+        let child = world.new_entity().with(Health(10)).build();
+        let grandchild = world.new_entity().build();
+        world.add_child(root, child);
+        world.add_child(child, grandchild);
+        assert_eq!(
+            world.get_component::<Health>(root).as_deref(),
+            Some(&Health(101))
+        );
+
+        fn take_damage(world: &mut World, trigger: &mut Trigger<Damage>) {
+            let key = trigger.target().unwrap();
+            if let Some(mut health) = world.get_component_mut::<Health>(key) {
+                health.0 -= trigger.event().0;
+                // the first entity with health absorbs the damage
+                trigger.propagate(false);
+            }
+        }
+        world.observe_entity(root, take_damage);
+        world.observe_entity(child, take_damage);
+        world.observe_entity(grandchild, take_damage);
+
+        world.trigger_targets(Damage(5), grandchild);
+        assert_eq!(
+            world.get_component::<Health>(child).as_deref(),
+            Some(&Health(6))
+        );
+        assert_eq!(
+            world.get_component::<Health>(root).as_deref(),
+            Some(&Health(101))
+        );
+
+        world.remove_component::<Health>(child);
+        let mut commands = Commands::default();
+        commands.trigger_targets(Damage(50), grandchild);
+        commands.run_commands(&mut world);
+        assert_eq!(
+            world.get_component::<Health>(root).as_deref(),
+            Some(&Health(51))
+        );
+
+        world.remove_entity(root);
+        assert_eq!(
+            world.get_resource::<Removed>().as_deref(),
+            Some(&Removed(2))
+        );
+    }
+
+    #[test]
+    fn removed_components() {
+        use crate::removed::RemovedComponents;
+
+        make_component! {
+            struct Body;
+        }
+
+        let mut world = World::new();
+
+        world.register::<Body>();
+        world.track_removals::<Body>();
+
+        let e1 = world.new_entity().with(Body).build();
+        let e2 = world.new_entity().with(Body).build();
+        let e3 = world.new_entity().build();
+
+        let mut removed = RemovedComponents::<Body>::default();
+        assert_eq!(removed.read(&world).count(), 0);
+
+        world.remove_component::<Body>(e1);
+        world.remove_component::<Body>(e1);
+        world.remove_entity(e2);
+        world.remove_entity(e3);
+        assert_eq!(removed.read(&world).collect::<Vec<_>>(), vec![e1, e2]);
+        assert_eq!(removed.read(&world).count(), 0);
+
+        // a reader that did not read last frame still sees last frame's removals
+        world.update_removed();
+        let mut late = RemovedComponents::<Body>::default();
+        assert_eq!(late.read(&world).count(), 2);
+
+        world.update_removed();
+        assert_eq!(RemovedComponents::<Body>::default().read(&world).count(), 0);
+
+        // untracked types record nothing
+        make_component! {
+            struct Shape;
+        }
+        let e4 = world.new_entity().with(Shape).build();
+        world.remove_entity(e4);
+        assert_eq!(RemovedComponents::<Shape>::default().read(&world).count(), 0);
+        assert_eq!(world.removed.len(), 1);
+    }
+
+    #[test]
+    fn type_registry() {
+        make_component! {
+            #[derive(Debug, Default, Clone, PartialEq)]
+            struct Health(u32);
+        }
+        make_component! {
+            struct Marker;
+        }
+
+        let mut world = World::new();
+
+        world
+            .register_cloneable::<Health>()
+            .named("Health")
+            .reflect_default()
+            .reflect_debug();
+        world.register::<Marker>();
+
+        let info = world.type_registry().get("Health").unwrap();
+        assert_eq!(info.type_id(), std::any::TypeId::of::<Health>());
+        assert_eq!(info.size(), std::mem::size_of::<u32>());
+        assert_eq!(
+            info.default_value().unwrap().downcast_ref::<Health>(),
+            Some(&Health(0))
+        );
+        assert_eq!(
+            info.clone_value(&Health(3))
+                .unwrap()
+                .downcast_ref::<Health>(),
+            Some(&Health(3))
+        );
+        assert!(world.type_registry().get("Marker").is_none());
+        let marker = world.type_registry().get_of::<Marker>().unwrap();
+        assert_eq!(marker.name(), std::any::type_name::<Marker>());
+        assert!(marker.default_value().is_none());
+
+        let entity = world.new_entity().with(Marker).build();
+        assert!(world.add_component_by_name(entity, "Health", Box::new(Health(10))));
+        assert!(!world.add_component_by_name(entity, "Mana", Box::new(Health(10))));
+        assert_eq!(
+            world
+                .get_component_by_name(entity, "Health")
+                .unwrap()
+                .downcast_ref::<Health>(),
+            Some(&Health(10))
+        );
+        assert_eq!(
+            world.debug_component(entity, "Health").as_deref(),
+            Some("Health(10)")
+        );
+        assert!(world
+            .debug_component(entity, std::any::type_name::<Marker>())
+            .is_none());
+
+        let names = world.component_names(entity);
+        assert!(names.contains(&"Health"));
+        assert!(names.contains(&std::any::type_name::<Marker>()));
+
+        // registering again keeps the configuration
+        world.register::<Health>();
+        assert!(world.type_registry().get("Health").is_some());
+    }
+
+    #[test]
+    fn dynamic_components() {
+        use crate::dynamic::{ComponentDescriptor, DynamicQuery};
+        use std::alloc::Layout;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        make_component! {
+            struct Player;
+        }
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        let mut world = World::new();
+
+        world.register::<Player>();
+        let mana = world.register_dynamic(ComponentDescriptor::new("Mana", Layout::new::<u64>()));
+        let curse = world.register_dynamic(
+            ComponentDescriptor::new("Curse", Layout::new::<u32>())
+                .with_drop(|_| _ = DROPPED.fetch_add(1, Ordering::Relaxed)),
+        );
+        assert_eq!(world.dynamic_component_id("Mana"), Some(mana));
+        assert_eq!(world.component_descriptor(curse).unwrap().name(), "Curse");
+
+        let player = world.new_entity().with(Player).build();
+        let monster = world.new_entity().build();
+        world.add_component_by_id(player, mana, &10u64.to_ne_bytes());
+        world.add_component_by_id(monster, mana, &5u64.to_ne_bytes());
+        world.add_component_by_id(monster, curse, &1u32.to_ne_bytes());
+
+        let bytes = world.get_component_by_id(player, mana).unwrap();
+        assert_eq!(bytes.as_ptr().align_offset(8), 0);
+        assert_eq!(u64::from_ne_bytes((*bytes).try_into().unwrap()), 10);
+        drop(bytes);
+        world
+            .get_component_by_id_mut(player, mana)
+            .unwrap()
+            .copy_from_slice(&20u64.to_ne_bytes());
+        assert_eq!(
+            &*world.get_component_by_id(player, mana).unwrap(),
+            &20u64.to_ne_bytes()
+        );
+
+        let player_id = world.component_id::<Player>().unwrap();
+        let query = DynamicQuery::new().with(mana).without(player_id);
+        assert_eq!(query.iter(&world).collect::<Vec<_>>(), vec![monster]);
+        let query = DynamicQuery::new().with(mana).with(player_id);
+        assert_eq!(query.iter(&world).collect::<Vec<_>>(), vec![player]);
+
+        // replacing, removing and despawning all drop the old value
+        world.add_component_by_id(monster, curse, &2u32.to_ne_bytes());
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+        world.remove_entity(monster);
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 2);
+        assert!(!world.contains_component_by_id(monster, mana));
+        world.add_component_by_id(player, curse, &3u32.to_ne_bytes());
+        assert!(world.remove_component_by_id(player, curse));
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 3);
+
+        // Rust components are not exposed as bytes
+        assert!(world.contains_component_by_id(player, player_id));
+        assert!(world.get_component_by_id(player, player_id).is_none());
+
+        // ids this world never registered match nothing
+        let mut other = World::new();
+        for name in ["A", "B", "C"] {
+            other.register_dynamic(ComponentDescriptor::new(name, Layout::new::<u8>()));
+        }
+        let foreign = other.dynamic_component_id("C").unwrap();
+        assert!(!world.add_component_by_id(player, foreign, &[1]));
+        assert!(!world.contains_component_by_id(player, foreign));
+        assert!(world.get_component_by_id_mut(player, foreign).is_none());
+        assert!(!world.remove_component_by_id(player, foreign));
+    }
+
+    #[test]
+    fn entity_bits_and_copying() {
+        use crate::entity_map::{EntityMap, MapEntities};
+
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Name(&'static str);
+        }
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Target(EntityId);
+        }
+        impl MapEntities for Target {
+            fn map_entities(&mut self, map: &EntityMap) {
+                self.0.map_entities(map);
+            }
+        }
+
+        // names of the targets seen by the `Target` hook
+        make_component! {
+            #[derive(Default)]
+            struct Seen(Vec<Option<&'static str>>);
+        }
+
+        fn setup() -> World {
+            let mut world = World::new();
+
+            world.register_cloneable::<Name>();
+            world
+                .register_cloneable::<Target>()
+                .map_entities()
+                .on_insert(|world, key| {
+                    let target = world.get_component::<Target>(key).unwrap().0;
+                    let name = world.get_component::<Name>(target).map(|name| name.0);
+                    if let Some(mut seen) = world.get_resource_mut::<Seen>() {
+                        seen.0.push(name);
+                    }
+                });
+
+            world
+        }
+
+        let mut source = setup();
+        let removed = source.new_entity().build();
+        source.remove_entity(removed);
+        let ship = source.new_entity().with(Name("ship")).build();
+        let turret = source.new_entity().with(Name("turret")).build();
+        let enemy = source.new_entity().with(Name("enemy")).build();
+        source.add_child(ship, turret);
+        source.add_component(turret, Target(enemy));
+        source.add_component(ship, Target(turret));
+
+        for key in [ship, turret, removed, EntityId::default()] {
+            assert_eq!(EntityId::from_bits(key.to_bits()), key);
+        }
+        assert_ne!(ship.to_bits(), removed.to_bits());
+        assert!(!source.contains_component::<EntityId>(EntityId::from_bits(removed.to_bits())));
+
+        let mut world = setup();
+        world.add_resource(Seen::default());
+        world.new_entity().build();
+        let map = world.copy_entities(&source, &[ship, turret]);
+        // the hook runs after the targets are remapped
+        assert_eq!(
+            world.get_resource::<Seen>().unwrap().0,
+            [Some("turret"), None]
+        );
+        assert_eq!(map.len(), 2);
+        let (new_ship, new_turret) = (map.map(ship), map.map(turret));
+        assert_ne!(new_ship, ship);
+        assert_eq!(
+            world.get_component::<Name>(new_turret).as_deref(),
+            Some(&Name("turret"))
+        );
+        assert_eq!(world.parent(new_turret), Some(new_ship));
+        assert_eq!(world.children(new_ship), vec![new_turret]);
+        assert_eq!(
+            world.get_component::<Target>(new_ship).unwrap().0,
+            new_turret
+        );
+        // the enemy was not copied, so the turret no longer points at anything
+        assert_eq!(
+            world.get_component::<Target>(new_turret).unwrap().0,
+            EntityId::default()
+        );
+        assert_eq!(source.get_component::<Target>(turret).unwrap().0, enemy);
+    }
+
+    #[test]
+    fn snapshots() {
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Position(i64);
+        }
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Velocity(i64);
+        }
+        make_component! {
+            #[derive(Default)]
+            struct Sprite;
+        }
+        // a tiny deterministic random number generator
+        make_component! {
+            #[derive(Clone)]
+            struct Rng(u64);
+        }
+
+        fn step(world: &mut World) {
+            let roll = {
+                let mut rng = world.get_resource_mut::<Rng>().unwrap();
+                rng.0 = rng.0.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (rng.0 >> 33) as i64
+            };
+
+            for (mut position, velocity) in world.query_mut::<(Position, Velocity)>() {
+                position.0 += velocity.0;
+            }
+            if roll % 3 == 0 {
+                world
+                    .new_entity()
+                    .with(Position(roll % 100))
+                    .with(Velocity(roll % 7 - 3))
+                    .with(Sprite)
+                    .build();
+            }
+            if roll % 5 == 0 {
+                let oldest = world.query::<EntityId>().map(|id| *id).next();
+                if let Some(oldest) = oldest {
+                    world.remove_entity(oldest);
+                }
+            }
+        }
+
+        fn state(world: &World) -> Vec<(EntityId, Option<EntityId>, i64, i64)> {
+            let mut state = world
+                .query::<(EntityId, Position, Velocity)>()
+                .map(|(id, position, velocity)| (*id, world.parent(*id), position.0, velocity.0))
+                .collect::<Vec<_>>();
+            state.sort();
+            state
+        }
+
+        let mut world = World::new();
+
+        world.register_cloneable::<Position>();
+        world.register_cloneable::<Velocity>().requires::<Sprite>();
+        world.register::<Sprite>();
+        world.register_cloneable_resource::<Rng>();
+        world.add_resource(Rng(7));
+        let root = world
+            .new_entity()
+            .with(Position(0))
+            .with(Velocity(1))
+            .with(Sprite)
+            .build();
+        for i in 1..5 {
+            let child = world
+                .new_entity()
+                .with(Position(i))
+                .with(Velocity(1))
+                .with(Sprite)
+                .build();
+            world.add_child(root, child);
+        }
+        for _ in 0..10 {
+            step(&mut world);
+        }
+
+        let snapshot = world.snapshot();
+        let before = state(&world);
+        let mut runs = Vec::new();
+        for _ in 0..3 {
+            world.restore(&snapshot);
+            assert_eq!(state(&world), before);
+
+            let mut states = Vec::new();
+            for _ in 0..30 {
+                step(&mut world);
+                states.push(state(&world));
+            }
+            // entities spawned after the restore get the same ids every time
+            states.push(vec![(world.new_entity().build(), None, 0, 0)]);
+            runs.push(states);
+        }
+        assert!(runs.iter().all(|run| *run == runs[0]));
+
+        world.restore(&snapshot);
+        assert_eq!(state(&world), before);
+        // uncloneable components of surviving entities are kept, and required ones are added back to the others
+        assert!(before
+            .iter()
+            .all(|(id, _, _, _)| world.contains_component::<Sprite>(*id)));
+        assert_eq!(
+            world.query::<EntityId>().count(),
+            world.query::<Sprite>().count()
+        );
+
+        // a removed entity loses its uncloneable components, so the required ones are added back on restore
+        let removed = before[0].0;
+        world.remove_entity(removed);
+        world.restore(&snapshot);
+        assert!(world.contains_component::<Sprite>(removed));
+    }
+
+    #[test]
+    fn plugins() {
+        use crate::{
+            app::App,
+            plugin::Plugin,
+            transform::{GlobalTransform, Transform, TransformPlugin},
+        };
+
+        make_component! {
+            struct Log(Vec<&'static str>);
+        }
+
+        fn log(world: &mut World, message: &'static str) {
+            world.get_resource_mut::<Log>().unwrap().0.push(message);
+        }
+
+        struct LogPlugin;
+        impl Plugin for LogPlugin {
+            fn build(&self, app: &mut App) {
+                app.add_startup_system(|world| {
+                    world.add_resource(Log(Vec::new()));
+                });
+            }
+        }
+
+        struct InputPlugin;
+        impl Plugin for InputPlugin {
+            fn build(&self, app: &mut App) {
+                app.add_system(|world| log(world, "input"));
+            }
+
+            fn dependencies(&self) -> Vec<Box<dyn Plugin>> {
+                vec![Box::new(LogPlugin)]
+            }
+        }
+
+        struct PhysicsPlugin;
+        impl Plugin for PhysicsPlugin {
+            fn build(&self, app: &mut App) {
+                app.add_system(|world| log(world, "physics"));
+            }
+
+            fn dependencies(&self) -> Vec<Box<dyn Plugin>> {
+                vec![Box::new(LogPlugin), Box::new(InputPlugin)]
+            }
+        }
+
+        struct ShutdownPlugin;
+        impl Plugin for ShutdownPlugin {
+            fn build(&self, app: &mut App) {
+                app.add_startup_system(|world| {
+                    world.new_entity().with(Transform::default()).build();
+                })
+                .add_system(|world| {
+                    let log = world.get_resource::<Log>().unwrap().0.clone();
+                    // dependencies are built before the plugins that need them, and only once
+                    assert_eq!(log, vec!["input", "physics"]);
+                    // one entity for each time this plugin was added
+                    assert_eq!(world.query::<GlobalTransform>().count(), 2);
+                    world.shutdown();
+                });
+            }
+
+            fn is_unique(&self) -> bool {
+                false
+            }
+        }
+
+        let mut app = App::new();
+        // `InputPlugin` was already added as a dependency of `PhysicsPlugin`, so adding it afterwards does nothing
+        app.add_plugins((PhysicsPlugin, InputPlugin, TransformPlugin))
+            .add_plugin(ShutdownPlugin)
+            .add_plugin(ShutdownPlugin);
+        assert!(app.has_plugin::<LogPlugin>());
+        assert!(app.has_plugin::<InputPlugin>());
+        assert!(app.has_plugin::<TransformPlugin>());
+        app.run();
+    }
+
+    #[test]
+    #[should_panic(expected = "was already added")]
+    fn duplicate_plugin() {
+        use crate::{app::App, transform::TransformPlugin};
+
+        App::new()
+            .add_plugin(TransformPlugin)
+            .add_plugins((TransformPlugin,));
+    }
+
+    #[test]
+    fn app_update() {
+        use crate::{app::App, event::Events};
+
+        make_component! {
+            struct Position(i32);
+        }
+        make_component! {
+            struct Velocity(i32);
+        }
+        struct Bounced;
+
+        fn setup(world: &mut World) {
+            world.register::<Position>();
+            world.register::<Velocity>();
+            world.get_commands().add_command(|world| {
+                world
+                    .new_entity()
+                    .with(Position(0))
+                    .with(Velocity(2))
+                    .build();
+            });
+        }
+
+        fn movement(world: &mut World) {
+            for (mut position, mut velocity) in world.query_mut::<(Position, Velocity)>() {
+                position.0 += velocity.0;
+                if position.0.abs() >= 4 {
+                    velocity.0 = -velocity.0;
+                    world.send_event(Bounced);
+                }
+            }
+        }
+
+        let mut app = App::new();
+        app.add_event::<Bounced>()
+            .add_startup_system(setup)
+            .add_system(movement);
+
+        app.startup();
+        app.startup();
+        let position = |app: &App| app.world().query_single::<Position>().unwrap().0;
+        assert_eq!(position(&app), 0);
+
+        let mut positions = Vec::new();
+        for _ in 0..5 {
+            app.update();
+            positions.push(position(&app));
+        }
+        assert_eq!(positions, vec![2, 4, 2, 0, -2]);
+        // the bounce in the second frame was dropped by the event swaps since
+        assert!(app
+            .world()
+            .get_resource::<Events<Bounced>>()
+            .unwrap()
+            .is_empty());
+
+        app.world_mut().query_single_mut::<Velocity>().unwrap().0 = 0;
+        app.update();
+        assert_eq!(position(&app), -2);
+    }
+
+    #[test]
+    fn runners() {
+        use crate::{
+            app::App,
+            runner::{run_fixed_rate, run_frames, run_once},
+        };
+        use std::time::{Duration, Instant};
+
+        make_component! {
+            struct Frames(u32);
+        }
+        make_component! {
+            struct Shutdowns(u32);
+        }
+
+        fn count(world: &mut World) {
+            let frames = {
+                let mut frames = world.get_resource_mut::<Frames>().unwrap();
+                frames.0 += 1;
+                frames.0
+            };
+            if frames == 5 {
+                world.shutdown();
+            }
+        }
+
+        fn app() -> App {
+            let mut app = App::new();
+            app.add_startup_system(|world| {
+                world.add_resource(Frames(0));
+                world.add_resource(Shutdowns(0));
+            })
+            .add_system(count)
+            .add_shutdown_system(|world| {
+                world.get_resource_mut::<Shutdowns>().unwrap().0 += 1;
+            });
+            app
+        }
+
+        fn frames(app: &App) -> u32 {
+            app.world().get_resource::<Frames>().unwrap().0
+        }
+
+        fn shutdowns(app: &App) -> u32 {
+            app.world().get_resource::<Shutdowns>().unwrap().0
+        }
+
+        let mut once = app();
+        once.set_runner(run_once).run();
+        assert_eq!(frames(&once), 1);
+        assert_eq!(shutdowns(&once), 1);
+
+        let mut three = app();
+        three.set_runner(run_frames(3)).run();
+        assert_eq!(frames(&three), 3);
+        // out of frames, the runner shut the app down
+        assert!(!three.world().active());
+        assert_eq!(shutdowns(&three), 1);
+        // the runner is used up; running again falls back to the default loop
+        three.world_mut().cancel_exit();
+        three.run();
+        assert_eq!(frames(&three), 5);
+        assert_eq!(shutdowns(&three), 2);
+
+        let mut stopped = app();
+        stopped.set_runner(run_frames(100)).run();
+        assert_eq!(frames(&stopped), 5);
+        assert_eq!(shutdowns(&stopped), 1);
+
+        let start = Instant::now();
+        let mut fixed = app();
+        fixed
+            .set_runner(run_fixed_rate(Duration::from_millis(10)))
+            .run();
+        assert_eq!(frames(&fixed), 5);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        let mut custom = app();
+        custom
+            .set_runner(|app| {
+                app.update();
+                app.update();
+            })
+            .run();
+        assert_eq!(frames(&custom), 2);
+    }
+
+    #[test]
+    fn app_exit() {
+        use crate::app::{App, AppExit};
+
+        make_component! {
+            struct Frames(u32);
+        }
+        make_component! {
+            struct Saves(u32);
+        }
+        make_component! {
+            struct Unsaved(bool);
+        }
+
+        fn count(world: &mut World) {
+            let frames = {
+                let mut frames = world.get_resource_mut::<Frames>().unwrap();
+                frames.0 += 1;
+                frames.0
+            };
+            if frames == 3 {
+                world.exit(AppExit::Error(2));
+            }
+            if frames == 5 {
+                world.exit(AppExit::Error(3));
+            }
+        }
+
+        fn save(world: &mut World) {
+            world.get_resource_mut::<Saves>().unwrap().0 += 1;
+        }
+
+        fn veto_once(world: &mut World) {
+            let mut unsaved = world.get_resource_mut::<Unsaved>().unwrap();
+            if unsaved.0 {
+                unsaved.0 = false;
+                drop(unsaved);
+                world.cancel_exit();
+            }
+        }
+
+        let mut app = App::new();
+        app.add_startup_system(|world| {
+            world.add_resource(Frames(0));
+            world.add_resource(Saves(0));
+            world.add_resource(Unsaved(true));
+        })
+        .add_system(count)
+        .add_shutdown_system(save)
+        .add_shutdown_system(veto_once);
+
+        let exit = app.run();
+        assert_eq!(exit, AppExit::Error(3));
+        assert_eq!(exit.code(), 3);
+        assert!(!exit.is_success());
+        assert!(!app.world().active());
+        // the first exit was vetoed, and the shutdown systems ran for both requests
+        assert_eq!(app.world().get_resource::<Frames>().unwrap().0, 5);
+        assert_eq!(app.world().get_resource::<Saves>().unwrap().0, 2);
+
+        let mut app = App::new();
+        app.add_system(|world| world.shutdown());
+        assert_eq!(app.run(), AppExit::Success);
+        assert!(AppExit::Success.is_success());
+
+        // an exit requested by a startup system runs the shutdown systems before any frame, and they can veto it
+        fn startup_app(unsaved: bool) -> App {
+            let mut app = App::new();
+            app.world_mut().add_resource(Unsaved(unsaved));
+            app.add_startup_system(|world| {
+                world.add_resource(Frames(0));
+                world.add_resource(Saves(0));
+                world.shutdown();
+            })
+            .add_system(count)
+            .add_shutdown_system(save)
+            .add_shutdown_system(veto_once);
+            app
+        }
+
+        let mut app = startup_app(false);
+        assert_eq!(app.run(), AppExit::Success);
+        assert_eq!(app.world().get_resource::<Frames>().unwrap().0, 0);
+        assert_eq!(app.world().get_resource::<Saves>().unwrap().0, 1);
+
+        let mut app = startup_app(true);
+        assert_eq!(app.run(), AppExit::Error(2));
+        assert_eq!(app.world().get_resource::<Frames>().unwrap().0, 3);
+        assert_eq!(app.world().get_resource::<Saves>().unwrap().0, 2);
+    }
+
+    #[test]
+    fn sub_apps() {
+        use crate::{
+            app::{App, AppExit},
+            sub_app::{extract_component, ExtractedEntities},
+        };
+
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Position(i32);
+        }
+        make_component! {
+            struct Drawn(Vec<i32>);
+        }
+
+        fn movement(world: &mut World) {
+            for mut position in world.query_mut::<Position>() {
+                position.0 += 1;
+            }
+        }
+
+        fn draw(world: &mut World) {
+            let mut positions = world.query::<Position>().map(|p| p.0).collect::<Vec<_>>();
+            positions.sort();
+            world.add_resource(Drawn(positions));
+        }
+
+        let mut render = App::new();
+        render.add_system(draw);
+        let mut server = App::new();
+        server.add_startup_system(|world| {
+            world.add_resource(Drawn(vec![-1]));
+        });
+
+        let mut app = App::new();
+        app.add_startup_system(|world| {
+            world.register::<Position>();
+            world.new_entity().with(Position(0)).build();
+            world.new_entity().with(Position(10)).build();
+        })
+        .add_system(movement)
+        .add_sub_app("render", render, |main, sub| {
+            extract_component::<Position>(main, sub)
+        })
+        .add_sub_app("server", server, |_, _| {});
+
+        let drawn = |app: &App| {
+            let render = app.sub_app("render").unwrap();
+            let drawn = render.world().get_resource::<Drawn>().unwrap().0.clone();
+            drawn
+        };
+
+        app.update();
+        assert_eq!(drawn(&app), vec![1, 11]);
+        app.update();
+        assert_eq!(drawn(&app), vec![2, 12]);
+
+        // removals in the main world reach the sub-app
+        let first = app
+            .world()
+            .query::<EntityId>()
+            .map(|id| *id)
+            .next()
+            .unwrap();
+        app.world_mut().remove_entity(first);
+        app.update();
+        assert_eq!(drawn(&app), vec![13]);
+        let render = app.sub_app("render").unwrap();
+        assert_eq!(render.world().query::<EntityId>().count(), 1);
+        let extracted = render.world().get_resource::<ExtractedEntities>().unwrap();
+        assert_eq!(extracted.0.len(), 1);
+        drop(extracted);
+
+        // sub-apps are independent worlds
+        assert!(app
+            .sub_app_mut("server")
+            .unwrap()
+            .world()
+            .query::<Position>()
+            .next()
+            .is_none());
+        assert_eq!(
+            app.sub_app("server")
+                .unwrap()
+                .world()
+                .get_resource::<Drawn>()
+                .unwrap()
+                .0,
+            vec![-1]
+        );
+        assert!(app.sub_app("client").is_none());
+
+        // exits requested by a sub-app are cleared, and its shutdown systems only run once the main app exits
+        make_component! {
+            #[derive(Default)]
+            struct Shutdowns(Vec<Option<AppExit>>);
+        }
+
+        let mut menu = App::new();
+        menu.add_startup_system(|world| {
+            world.add_resource(Shutdowns::default());
+        })
+        .add_system(|world| world.exit(AppExit::Error(1)))
+        .add_shutdown_system(|world| {
+            let exit = world.exit_requested();
+            world.get_resource_mut::<Shutdowns>().unwrap().0.push(exit);
+        });
+        app.add_sub_app("menu", menu, |_, _| {});
+
+        let shutdowns = |app: &App| {
+            let menu = app.sub_app("menu").unwrap();
+            let shutdowns = menu.world().get_resource::<Shutdowns>().unwrap().0.clone();
+            shutdowns
+        };
+
+        app.update();
+        app.update();
+        assert!(app.world().active());
+        assert!(app.sub_app("menu").unwrap().world().active());
+        assert!(shutdowns(&app).is_empty());
+
+        app.world_mut().exit(AppExit::Error(2));
+        app.update();
+        assert_eq!(shutdowns(&app), vec![Some(AppExit::Error(2))]);
+        app.update();
+        assert_eq!(shutdowns(&app).len(), 1);
+    }
+
+    #[test]
+    fn implicit_registration() {
+        make_component! {
+            #[derive(Debug, Clone, PartialEq)]
+            struct Score(u32);
+        }
+        make_component! {
+            struct Added;
+        }
+        struct Likes;
+
+        let mut world = World::new();
+        let player = world.new_entity().with(Score(3)).build();
+        assert!(world.contains_storage::<Score>());
+        assert!(world.type_registry().get_of::<Score>().is_some());
+
+        // registering again keeps the components
+        world.register::<Score>().on_add(|world, _| {
+            world.add_resource(Added);
+        });
+        assert_eq!(world.get_component::<Score>(player).unwrap().0, 3);
+        world.register::<Score>();
+        let other = world.new_entity().with(Score(5)).build();
+        assert!(world.get_resource::<Added>().is_some());
+
+        // and can make the storage cloneable
+        assert_eq!(
+            world.uncloneable_components(player),
+            vec![std::any::type_name::<Score>()]
+        );
+        world.register_cloneable::<Score>();
+        let copy = world.clone_entity(player);
+        assert_eq!(*world.get_component::<Score>(copy).unwrap(), Score(3));
+        assert_eq!(world.get_component::<Score>(other).unwrap().0, 5);
+
+        world.add_relation(player, other, Likes);
+        world.register_relation::<Likes>();
+        assert!(world.has_relation::<Likes>(player, other));
+        world.remove_entity(other);
+        assert!(!world.has_relation::<Likes>(player, other));
+    }
+
+    #[test]
+    fn required_components() {
+        make_component! {
+            #[derive(Debug, Default, PartialEq)]
+            struct Position(i32);
+        }
+        make_component! {
+            #[derive(Debug, Default, PartialEq)]
+            struct Direction(i32);
+        }
+        make_component! {
+            #[derive(Debug, Default, PartialEq)]
+            struct Speed(i32);
+        }
+        make_component! {
+            #[derive(Default)]
+            struct Ping;
+        }
+        make_component! {
+            #[derive(Default)]
+            struct Pong;
+        }
+
+        let mut world = World::new();
+        world.register::<Speed>().requires::<Direction>();
+        world
+            .register::<Direction>()
+            .requires::<Position>()
+            .on_add(|world, key| {
+                // required components are there before the hooks run
+                assert!(world.contains_component::<Position>(key));
+            });
+        world.register::<Ping>().requires::<Pong>();
+        world.register::<Pong>().requires::<Ping>();
+
+        let runner = world.new_entity().with(Speed(2)).build();
+        assert_eq!(
+            *world.get_component::<Direction>(runner).unwrap(),
+            Direction(0)
+        );
+        assert_eq!(
+            *world.get_component::<Position>(runner).unwrap(),
+            Position(0)
+        );
+        assert_eq!(world.query_mut::<(Position, Direction, Speed)>().count(), 1);
+
+        // explicit values are kept, before or after the component requiring them
+        let walker = world
+            .new_entity()
+            .with(Position(5))
+            .with(Speed(1))
+            .with(Direction(-1))
+            .build();
+        assert_eq!(
+            *world.get_component::<Position>(walker).unwrap(),
+            Position(5)
+        );
+        assert_eq!(
+            *world.get_component::<Direction>(walker).unwrap(),
+            Direction(-1)
+        );
+
+        // replacing a value does not bring back removed requirements
+        world.remove_component::<Position>(walker);
+        world.add_component(walker, Direction(1));
+        assert!(!world.contains_component::<Position>(walker));
+
+        let ball = world.new_entity().with(Ping).build();
+        assert!(world.contains_component::<Pong>(ball));
+    }
+
+    #[test]
+    fn derived_components() {
+        use crate::component::Component;
+
+        make_component! {
+            #[derive(Debug, Default, PartialEq)]
+            struct Position(i32);
+        }
+        make_component! {
+            #[derive(Debug, Clone, Default, PartialEq)]
+            #[component(cloneable, requires(Position), name = "Velocity")]
+            #[component(reflect(Default, Debug), on_add = count_added)]
+            struct Velocity(i32);
+        }
+        make_component! {
+            struct Added(usize);
+        }
+        #[derive(Component, Clone, Debug, PartialEq)]
+        #[component(crate = crate, cloneable)]
+        struct Tagged<T>(T);
+
+        fn count_added(world: &mut World, _: EntityId) {
+            world.get_resource_mut::<Added>().unwrap().0 += 1;
+        }
+
+        let mut world = World::new();
+        world.add_resource(Added(0));
+        // configured without registering explicitly
+        let ball = world
+            .new_entity()
+            .with(Velocity(3))
+            .with(Tagged("ball"))
+            .build();
+        assert_eq!(*world.get_component::<Position>(ball).unwrap(), Position(0));
+        assert_eq!(world.get_resource::<Added>().unwrap().0, 1);
+
+        let info = world.type_registry().get("Velocity").unwrap();
+        assert_eq!(
+            info.default_value().unwrap().downcast_ref::<Velocity>(),
+            Some(&Velocity(0))
+        );
+        assert_eq!(
+            world.debug_component(ball, "Velocity").as_deref(),
+            Some("Velocity(3)")
+        );
+
+        // registering again does not configure twice
+        world.register::<Velocity>();
+        world.new_entity().with(Velocity(1)).build();
+        assert_eq!(world.get_resource::<Added>().unwrap().0, 2);
+
+        let copy = world.clone_entity(ball);
+        assert_eq!(*world.get_component::<Velocity>(copy).unwrap(), Velocity(3));
+        // `Position` is not cloneable, but required by `Velocity`
+        assert_eq!(*world.get_component::<Position>(copy).unwrap(), Position(0));
+        assert_eq!(
+            *world.get_component::<Tagged<&str>>(copy).unwrap(),
+            Tagged("ball")
+        );
+        assert!(world.get_component::<Tagged<i32>>(copy).is_none());
+    }
+
+    #[test]
+    fn storage_kinds() {
+        use crate::component::{Component, StorageKind};
+
+        #[derive(Component, Debug, PartialEq)]
+        #[component(crate = crate, storage = "sparse")]
+        struct Boss(u32);
+        make_component! {
+            #[derive(Debug, PartialEq)]
+            struct Health(u32);
+        }
+
+        let mut world = World::new();
+        let minion = world.new_entity().with(Health(10)).build();
+        let boss = world.new_entity().with(Health(100)).with(Boss(1)).build();
+        assert_eq!(
+            world.storage_mut::<Boss>().unwrap().kind(),
+            StorageKind::Sparse
+        );
+        assert_eq!(
+            world.storage_mut::<Health>().unwrap().kind(),
+            StorageKind::Dense
+        );
+
+        // switching keeps the components
+        world.register::<Health>().storage(StorageKind::Sparse);
+        assert_eq!(
+            world.storage_mut::<Health>().unwrap().kind(),
+            StorageKind::Sparse
+        );
+        assert_eq!(*world.get_component::<Health>(minion).unwrap(), Health(10));
+        assert_eq!(
+            world
+                .query::<(EntityId, Boss)>()
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>(),
+            vec![boss]
+        );
+
+        world.remove_component::<Boss>(boss);
+        assert!(!world.contains_component::<Boss>(boss));
+        assert_eq!(world.query::<Health>().count(), 2);
+    }
+
+    #[test]
+    fn bundles_and_system_params() {
+        use crate::{
+            app::App,
+            bundle::Bundle,
+            system_param::{QueryParam, SystemParam},
+        };
+        use std::cell::{Ref, RefMut};
+
+        make_component! {
+            #[derive(Debug, Default, PartialEq)]
+            struct Position(i32);
+        }
+        make_component! {
+            #[derive(Debug, PartialEq)]
+            struct Speed(i32);
+        }
+        make_component! {
+            struct Frozen;
+        }
+        make_component! {
+            struct Gravity(i32);
+        }
+        make_component! {
+            struct Moved(usize);
+        }
+
+        #[derive(Bundle)]
+        #[bundle(crate = crate)]
+        struct Body {
+            position: Position,
+            speed: Speed,
+        }
+        #[derive(Bundle)]
+        #[bundle(crate = crate)]
+        struct Statue(Body, Frozen);
+
+        #[derive(SystemParam)]
+        #[system_param(crate = crate)]
+        struct Physics<'w> {
+            gravity: Option<Ref<'w, Gravity>>,
+            moved: RefMut<'w, Moved>,
+        }
+        #[derive(SystemParam)]
+        #[system_param(crate = crate)]
+        struct Movement<'w> {
+            bodies: QueryParam<'w, (Position, Speed)>,
+            frozen: QueryParam<'w, (Frozen, Speed)>,
+            physics: Physics<'w>,
+        }
+
+        fn movement(mut params: Movement) {
+            let gravity = params.physics.gravity.map_or(0, |gravity| gravity.0);
+            for (mut position, speed) in params.bodies.iter_mut() {
+                position.0 += speed.0 + gravity;
+                params.physics.moved.0 += 1;
+            }
+            for (_, mut speed) in params.frozen.iter_mut() {
+                speed.0 = 0;
+            }
+        }
+
+        let mut app = App::new();
+        app.add_startup_system(|world| {
+            world.add_resource(Moved(0));
+            world
+                .new_entity()
+                .with_bundle(Body {
+                    position: Position(0),
+                    speed: Speed(2),
+                })
+                .build();
+            world
+                .new_entity()
+                .with_bundle(Statue(
+                    Body {
+                        position: Position(10),
+                        speed: Speed(1),
+                    },
+                    Frozen,
+                ))
+                .build();
+            world.new_entity().with_bundle((Speed(5), Frozen)).build();
+        })
+        .add_system_with::<Movement>(movement);
+
+        app.update();
+        let world = app.world();
+        let mut positions = world.query::<Position>().map(|p| p.0).collect::<Vec<_>>();
+        positions.sort();
+        assert_eq!(positions, vec![2, 11]);
+        assert_eq!(world.get_resource::<Moved>().unwrap().0, 2);
+        assert_eq!(
+            world
+                .query::<(Frozen, Speed)>()
+                .filter(|(_, speed)| speed.0 == 0)
+                .count(),
+            2
+        );
+
+        app.world_mut().add_resource(Gravity(-1));
+        app.update();
+        let mut positions = app
+            .world()
+            .query::<Position>()
+            .map(|p| p.0)
+            .collect::<Vec<_>>();
+        positions.sort();
+        assert_eq!(positions, vec![3, 10]);
+    }
+
+    #[test]
+    fn transferring_entities() {
+        use crate::entity_map::{EntityMap, MapEntities};
+
+        make_component! {
+            #[derive(Debug, PartialEq)]
+            struct Name(String);
+        }
+        make_component! {
+            struct Target(EntityId);
+        }
+        make_component! {
+            struct Health(f32);
+        }
+        make_component! {
+            struct Removals(usize);
+        }
+        make_component! {
+            struct Guards;
+        }
+        impl MapEntities for Target {
+            fn map_entities(&mut self, map: &EntityMap) {
+                self.0.map_entities(map);
+            }
+        }
+
+        let name = |world: &World, key| world.get_component::<Name>(key).unwrap().0.clone();
+
+        let mut main = World::new();
+        main.register::<Name>();
+        let player = main.new_entity().with(Name("player".into())).build();
+
+        // a chunk built on a background world
+        let mut chunk = World::new();
+        chunk.register::<Name>();
+        chunk.register::<Target>().map_entities();
+        chunk.register::<Removals>();
+        chunk.register::<Health>().on_remove(|world, _| {
+            world.get_resource_mut::<Removals>().unwrap().0 += 1;
+        });
+        chunk.add_resource(Removals(0));
+        let tower = chunk.new_entity().with(Name("tower".into())).build();
+        let archer = chunk
+            .new_entity()
+            .with(Name("archer".into()))
+            .with(Target(tower))
+            .with(Health(10.0))
+            .build();
+        let flag = chunk.new_entity().with(Name("flag".into())).build();
+        chunk.add_child(tower, archer);
+        chunk.add_child(tower, flag);
+        chunk.add_relation(archer, flag, Guards);
+        let tree = chunk.new_entity().with(Name("tree".into())).build();
+
+        let moved = chunk.transfer_entity(tower, &mut main);
+        assert!(!chunk.contains_component::<EntityId>(tower));
+        assert!(!chunk.contains_component::<EntityId>(archer));
+        assert_eq!(chunk.query::<Name>().count(), 1);
+        // moved components are not removed
+        assert_eq!(chunk.get_resource::<Removals>().unwrap().0, 0);
+
+        assert_eq!(name(&main, moved), "tower");
+        let children = main.children(moved);
+        assert_eq!(children.len(), 2);
+        assert_eq!(name(&main, children[0]), "archer");
+        assert_eq!(name(&main, children[1]), "flag");
+        // missing storages were registered, and IDs remapped
+        assert_eq!(main.get_component::<Target>(children[0]).unwrap().0, moved);
+        assert_eq!(main.get_component::<Health>(children[0]).unwrap().0, 10.0);
+        assert!(main.type_registry().get_of::<Target>().is_some());
+        assert_eq!(name(&main, player), "player");
+
+        // the hooks came along
+        main.add_resource(Removals(0));
+        main.remove_component::<Health>(children[0]);
+        assert_eq!(main.get_resource::<Removals>().unwrap().0, 1);
+
+        // relations keep being cleaned up in the new world
+        assert_eq!(main.sources::<Guards>(children[1]), vec![children[0]]);
+        main.remove_entity(children[1]);
+        assert!(main.targets::<Guards>(children[0]).is_empty());
+
+        let map = main.merge(chunk);
+        let tree = map.map(tree);
+        assert_eq!(name(&main, tree), "tree");
+        assert!(main.parent(tree).is_none());
+        assert_eq!(main.query::<Name>().count(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not exist")]
+    fn transferring_missing_entity() {
+        let mut main = World::new();
+        let mut chunk = World::new();
+        let gone = chunk.new_entity().build();
+        chunk.remove_entity(gone);
+
+        chunk.transfer_entity(gone, &mut main);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn world_serialization() {
+        use crate::entity_map::{EntityMap, MapEntities};
+        use serde::{Deserialize, Serialize};
+
+        make_component! {
+            #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+            struct Name(String);
+        }
+        make_component! {
+            #[derive(Debug, PartialEq, Serialize, Deserialize)]
+            struct Target(EntityId);
+        }
+        impl MapEntities for Target {
+            fn map_entities(&mut self, map: &EntityMap) {
+                self.0.map_entities(map);
+            }
+        }
+        make_component! {
+            #[derive(Debug, PartialEq, Serialize, Deserialize)]
+            struct Score(u32);
+        }
+        make_component! {
+            struct Handle;
+        }
+        make_component! {
+            #[derive(Debug, PartialEq, Serialize, Deserialize)]
+            struct Focus(EntityId);
+        }
+        impl MapEntities for Focus {
+            fn map_entities(&mut self, map: &EntityMap) {
+                self.0.map_entities(map);
+            }
+        }
+
+        // records what hooks see while loading
+        make_component! {
+            #[derive(Default)]
+            struct Log(Vec<String>);
+        }
+
+        fn log(world: &World, message: String) {
+            if let Some(mut log) = world.get_resource_mut::<Log>() {
+                log.0.push(message);
+            }
+        }
+
+        fn setup() -> World {
+            let mut world = World::new();
+
+            world
+                .register::<Target>()
+                .map_entities()
+                .requires::<Name>()
+                .serializable("Target")
+                .on_insert(|world, key| {
+                    let target = world.get_component::<Target>(key).unwrap().0;
+                    let name = world.get_component::<Name>(target).map(|name| name.0.clone());
+                    log(world, format!("target {name:?}"));
+                });
+            world
+                .register::<Name>()
+                .serializable("Name")
+                .on_insert(|world, key| {
+                    let name = world.get_component::<Name>(key).unwrap().0.clone();
+                    log(world, format!("name {name}"));
+                });
+            world.register::<Handle>();
+            world.register_serializable_resource::<Score>("Score");
+            world.register_serializable_resource_with_entities::<Focus>("Focus");
+
+            world
+        }
+
+        fn check(saved: &World, loaded: &World, map: &EntityMap) {
+            assert_eq!(map.len(), 3);
+            assert_eq!(loaded.get_resource::<Score>().as_deref(), Some(&Score(42)));
+            let focus = saved.get_resource::<Focus>().unwrap().0;
+            assert_eq!(loaded.get_resource::<Focus>().unwrap().0, map.map(focus));
+            for (old, new) in map.iter() {
+                assert_eq!(
+                    saved.get_component::<Name>(old).as_deref(),
+                    loaded.get_component::<Name>(new).as_deref()
+                );
+                assert_eq!(saved.parent(old).map(|id| map.map(id)), loaded.parent(new));
+                assert_eq!(
+                    saved
+                        .get_component::<Target>(old)
+                        .map(|target| map.map(target.0)),
+                    loaded.get_component::<Target>(new).map(|target| target.0)
+                );
+                assert!(!loaded.contains_component::<Handle>(new));
+            }
+        }
+
+        let mut world = setup();
+        // leave a hole in the allocator so saved and loaded ids differ
+        let hole = world.new_entity().build();
+        let player = world
+            .new_entity()
+            .with(Name("player".to_string()))
+            .with(Handle)
+            .build();
+        let enemy = world
+            .new_entity()
+            .with(Name("enemy".to_string()))
+            .with(Target(player))
+            .build();
+        let sword = world.new_entity().with(Name("sword".to_string())).build();
+        world.add_child(player, sword);
+        world.add_component(player, Target(enemy));
+        world.add_resource(Score(42));
+        world.add_resource(Focus(enemy));
+        world.remove_entity(hole);
+
+        let json = serde_json::to_string(&world.serializer()).unwrap();
+        let mut loaded = setup();
+        loaded.add_resource(Log::default());
+        let map = loaded
+            .deserialize(&mut serde_json::Deserializer::from_str(&json))
+            .unwrap();
+        check(&world, &loaded, &map);
+
+        // hooks run once per component, after the IDs are remapped
+        let mut log = loaded.get_resource::<Log>().unwrap().0.clone();
+        log.sort();
+        assert_eq!(
+            log,
+            [
+                "name enemy",
+                "name player",
+                "name sword",
+                "target Some(\"enemy\")",
+                "target Some(\"player\")"
+            ]
+        );
+
+        let ron = ron::to_string(&world.serializer()).unwrap();
+        let mut loaded = setup();
+        let map = loaded
+            .deserialize(&mut ron::Deserializer::from_str(&ron).unwrap())
+            .unwrap();
+        check(&world, &loaded, &map);
+
+        // a world without the registrations rejects the file
+        let mut unknown = World::new();
+        assert!(unknown
+            .deserialize(&mut serde_json::Deserializer::from_str(&json))
+            .is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn scenes() {
+        use crate::entity_map::{EntityMap, MapEntities};
+        use crate::transform::{Transform, Vec2};
+        use serde::{Deserialize, Serialize};
+
+        make_component! {
+            #[derive(Debug, PartialEq, Serialize, Deserialize)]
+            struct Name(String);
+        }
+        make_component! {
+            #[derive(Debug, PartialEq, Serialize, Deserialize)]
+            struct Target(EntityId);
+        }
+        impl MapEntities for Target {
+            fn map_entities(&mut self, map: &EntityMap) {
+                self.0.map_entities(map);
+            }
+        }
+
+        // names of the targets seen by the `Target` hook
+        make_component! {
+            #[derive(Default)]
+            struct Seen(Vec<Option<String>>);
+        }
+
+        fn setup() -> World {
+            let mut world = World::new();
+
+            world.register::<Transform>();
+            world.register::<Name>().serializable("Name");
+            world
+                .register::<Target>()
+                .map_entities()
+                .serializable("Target")
+                .on_insert(|world, key| {
+                    let target = world.get_component::<Target>(key).unwrap().0;
+                    let name = world.get_component::<Name>(target).map(|name| name.0.clone());
+                    if let Some(mut seen) = world.get_resource_mut::<Seen>() {
+                        seen.0.push(name);
+                    }
+                });
+
+            world
+        }
+
+        fn name(world: &World, key: EntityId) -> String {
+            world.get_component::<Name>(key).unwrap().0.clone()
+        }
+
+        let mut world = setup();
+        let roots = world
+            .spawn_scene(
+                r#"(
+                    entities: [
+                        (
+                            components: {
+                                "Name": ("player"),
+                                "Transform": (translation: (x: 1.0, y: 2.0), rotation: 0.5, scale: (x: 1.0, y: 1.0)),
+                            },
+                            children: [
+                                (components: { "Name": ("sword") }),
+                                (components: { "Name": ("shield") }),
+                            ],
+                        ),
+                        (components: { "Name": ("enemy") }),
+                    ],
+                )"#,
+            )
+            .unwrap();
+        let [player, enemy] = roots[..] else {
+            panic!("expected two roots");
+        };
+        assert_eq!(name(&world, player), "player");
+        assert_eq!(name(&world, enemy), "enemy");
+        assert_eq!(
+            world
+                .get_component::<Transform>(player)
+                .unwrap()
+                .translation,
+            Vec2::new(1.0, 2.0)
+        );
+        let children = world.children(player);
+        assert_eq!(children.len(), 2);
+        assert_eq!(name(&world, children[0]), "sword");
+        assert_eq!(name(&world, children[1]), "shield");
+        assert_eq!(world.parent(children[0]), Some(player));
+
+        // ids only need to be consistent within the file
+        world.add_component(enemy, Target(children[1]));
+        let saved = world.save_scene(&[enemy, player, children[0]]).unwrap();
+        let mut loaded = setup();
+        loaded.add_resource(Seen::default());
+        let roots = loaded.spawn_scene(&saved).unwrap();
+        let [enemy, player] = roots[..] else {
+            panic!("expected two roots");
+        };
+        // the hook runs after the target is remapped
+        assert_eq!(
+            loaded.get_resource::<Seen>().unwrap().0,
+            [Some("shield".to_string())]
+        );
+        let children = loaded.children(player);
+        assert_eq!(name(&loaded, children[1]), "shield");
+        assert_eq!(
+            loaded.get_component::<Target>(enemy).unwrap().0,
+            children[1]
+        );
+        assert_eq!(
+            loaded.get_component::<Transform>(player).unwrap().rotation,
+            0.5
+        );
+        assert_eq!(loaded.query::<EntityId>().count(), 4);
+
+        // a scene saved from a loaded scene loads back into exactly the same file
+        let resaved = loaded.save_scene(&roots).unwrap();
+        let mut reloaded = setup();
+        let roots = reloaded.spawn_scene(&resaved).unwrap();
+        assert_eq!(reloaded.save_scene(&roots).unwrap(), resaved);
+
+        assert!(loaded
+            .spawn_scene("(entities: [(components: { \"Mana\": (1) })])")
+            .is_err());
+        assert_eq!(loaded.query::<EntityId>().count(), 4);
+    }
 }
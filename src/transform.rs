@@ -0,0 +1,243 @@
+use crate::{
+    app::App,
+    component::{Component, EntityId},
+    hooks::ComponentRegistration,
+    plugin::Plugin,
+    world::World,
+};
+use std::ops::{Add, Mul, Sub};
+
+/// A plain 2D vector, so transforms can be used without pulling in a math or windowing crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+    pub const ONE: Self = Self::new(1.0, 1.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+/// A 2D affine transformation: a 2x2 linear part stored as column vectors, followed by a translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2 {
+    pub x_axis: Vec2,
+    pub y_axis: Vec2,
+    pub translation: Vec2,
+}
+
+impl Default for Affine2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine2 {
+    pub const IDENTITY: Self = Self {
+        x_axis: Vec2::new(1.0, 0.0),
+        y_axis: Vec2::new(0.0, 1.0),
+        translation: Vec2::ZERO,
+    };
+
+    /// Creates a transform that scales, then rotates by `angle` radians, then translates.
+    pub fn from_scale_angle_translation(scale: Vec2, angle: f32, translation: Vec2) -> Self {
+        let (sin, cos) = angle.sin_cos();
+
+        Self {
+            x_axis: Vec2::new(cos * scale.x, sin * scale.x),
+            y_axis: Vec2::new(-sin * scale.y, cos * scale.y),
+            translation,
+        }
+    }
+
+    /// Applies the linear part only, ignoring translation.
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        self.x_axis * vector.x + self.y_axis * vector.y
+    }
+
+    /// Applies the full transform to a point.
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.transform_vector(point) + self.translation
+    }
+}
+
+/// Composes two transforms; `a * b` applies `b` first, then `a`.
+impl Mul for Affine2 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self {
+            x_axis: self.transform_vector(rhs.x_axis),
+            y_axis: self.transform_vector(rhs.y_axis),
+            translation: self.transform_point(rhs.translation),
+        }
+    }
+}
+
+/// The placement of an entity relative to its parent, or to the world if it has no parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    pub translation: Vec2,
+    /// Rotation in radians.
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl Component for Transform {
+    fn configure(registration: &mut ComponentRegistration<'_, Self>) {
+        registration.cloneable();
+        #[cfg(feature = "serde")]
+        registration.serializable("Transform");
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vec2::ZERO,
+            rotation: 0.0,
+            scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Transform {
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self {
+            translation,
+            ..Default::default()
+        }
+    }
+
+    pub fn compute_affine(&self) -> Affine2 {
+        Affine2::from_scale_angle_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+/// The absolute placement of an entity, computed by `propagate_transforms`. This component is added automatically to every entity with a `Transform`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GlobalTransform {
+    pub(crate) affine: Affine2,
+    // what `affine` was computed from, used to detect dirty subtrees
+    local: Option<Transform>,
+    parent: Option<EntityId>,
+}
+
+impl Component for GlobalTransform {
+    fn configure(registration: &mut ComponentRegistration<'_, Self>) {
+        registration.cloneable();
+    }
+}
+
+impl GlobalTransform {
+    pub fn affine(&self) -> Affine2 {
+        self.affine
+    }
+
+    pub fn translation(&self) -> Vec2 {
+        self.affine.translation
+    }
+
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.affine.transform_point(point)
+    }
+}
+
+/// Registers `Transform` and `GlobalTransform`, and adds `propagate_transforms` as a system.
+/// Add it after the systems that move entities.
+pub struct TransformPlugin;
+
+impl Plugin for TransformPlugin {
+    fn build(&self, app: &mut App) {
+        // registered up front so scenes can refer to `Transform` by name
+        app.world_mut().register::<Transform>();
+        app.world_mut().register::<GlobalTransform>();
+        app.add_system(propagate_transforms);
+    }
+}
+
+/// System that updates `GlobalTransform` from `Transform` down the entity hierarchy.
+///
+/// An entity is recomputed only when its `Transform` or parent changed since the last run, or when an ancestor was recomputed.
+/// Entities whose parent has no `Transform` are treated as roots.
+pub fn propagate_transforms(world: &mut World) {
+    let missing = world
+        .query::<(EntityId, Transform)>()
+        .map(|(id, _)| *id)
+        .filter(|&id| !world.contains_component::<GlobalTransform>(id))
+        .collect::<Vec<_>>();
+    for id in missing {
+        world.add_component(id, GlobalTransform::default());
+    }
+
+    let roots = world
+        .query::<(EntityId, Transform)>()
+        .map(|(id, _)| *id)
+        .filter(|&id| {
+            world
+                .parent(id)
+                .is_none_or(|parent| !world.contains_component::<Transform>(parent))
+        })
+        .collect::<Vec<_>>();
+
+    // explicit stack instead of recursion so deep hierarchies can't overflow
+    let mut stack = roots
+        .into_iter()
+        .map(|root| (root, None, false))
+        .collect::<Vec<(EntityId, Option<(EntityId, Affine2)>, bool)>>();
+
+    while let Some((key, parent, parent_dirty)) = stack.pop() {
+        let Some((transform, mut global)) =
+            world.get_components_mut::<(Transform, GlobalTransform)>(key)
+        else {
+            continue;
+        };
+
+        let parent_id = parent.map(|(id, _)| id);
+        let dirty = parent_dirty || global.local != Some(*transform) || global.parent != parent_id;
+        if dirty {
+            let parent_affine = parent.map_or(Affine2::IDENTITY, |(_, affine)| affine);
+
+            global.affine = parent_affine * transform.compute_affine();
+            global.local = Some(*transform);
+            global.parent = parent_id;
+        }
+        let affine = global.affine;
+        drop((transform, global));
+
+        for child in world.children(key) {
+            stack.push((child, Some((key, affine)), dirty));
+        }
+    }
+}
@@ -1,9 +1,18 @@
 #![allow(unused)]
 use crate::{
+    app::AppExit,
     commands::Commands,
+    dynamic::{remove_dynamic_components, DynamicComponents},
     entity_builder::EntityBuilder,
-    component::{Component, ComponentStorage, EntityId},
+    entity_map::MapEntitiesFn,
+    event::Events,
+    component::{Component, ComponentStorage, EntityId, Storage},
+    hierarchy::{Children, Parent},
+    hooks::{ComponentHooks, ComponentRegistration, RequireFn},
+    observer::{remove_entity_observers, trigger_on_add, trigger_on_remove, AnyObserverList},
+    prefab::PrefabLink,
     query::Query,
+    registry::TypeRegistry,
 };
 use slotmap::HopSlotMap;
 use std::process::{Command, Output};
@@ -13,18 +22,53 @@ use std::{
     collections::HashMap,
 };
 
-type ComponentMap = HashMap<TypeId, Box<dyn Any>>;
+type ComponentMap = HashMap<TypeId, Box<dyn Storage>>;
 type ResourceMap = HashMap<TypeId, RefCell<Box<dyn Any>>>;
+pub(crate) type EntityIds = HopSlotMap<EntityId, ()>;
+pub(crate) type CleanupFn = fn(&mut World, EntityId);
+pub(crate) type CloneResourceFn = fn(&dyn Any) -> Box<dyn Any>;
+
+/// The storages of the components the world manages itself. They are skipped when copying or moving components between entities.
+pub(crate) fn managed_storages() -> [TypeId; 3] {
+    [
+        TypeId::of::<ComponentStorage<EntityId>>(),
+        TypeId::of::<ComponentStorage<Parent>>(),
+        TypeId::of::<ComponentStorage<Children>>(),
+    ]
+}
 
 #[derive(Default)]
 pub struct World {
     // has ComponentStorage<T>
-    components: ComponentMap,
+    pub(crate) components: ComponentMap,
     // has resources T
-    resources: ResourceMap,
+    pub(crate) resources: ResourceMap,
     // TODO: test whether SlotMap or HopSlotMap is faster
-    ids: HopSlotMap<EntityId, ()>,
-    active: bool,
+    pub(crate) ids: EntityIds,
+    // lifecycle hooks, keyed like `components`
+    pub(crate) hooks: HashMap<TypeId, ComponentHooks>,
+    // components added along with another, keyed like `components`, each with the storage ID of the required component
+    pub(crate) required: HashMap<TypeId, Vec<(TypeId, RequireFn)>>,
+    // `ObserverList<E>`, keyed by the `TypeId` of `E`
+    pub(crate) observers: HashMap<TypeId, Box<dyn AnyObserverList>>,
+    // entities that lost a component, keyed like `components`
+    pub(crate) removed: HashMap<TypeId, Events<EntityId>>,
+    // remaps entity IDs inside components, keyed like `components`
+    pub(crate) entity_mappers: HashMap<TypeId, MapEntitiesFn>,
+    // names and reflection metadata of registered components
+    pub(crate) registry: TypeRegistry,
+    // components registered at runtime without a Rust type
+    pub(crate) dynamic: DynamicComponents,
+    #[cfg(feature = "serde")]
+    pub(crate) serde: crate::serialize::SerdeRegistry,
+    // copies resources for `World::snapshot`, keyed like `resources`
+    pub(crate) resource_cloners: HashMap<TypeId, CloneResourceFn>,
+    // run for every removed entity, used to drop references to it
+    pub(crate) cleanups: Vec<CleanupFn>,
+    // like `cleanups`, but added for a component type and copied along with it by `World::transfer_entity`, keyed like `components`
+    pub(crate) storage_cleanups: HashMap<TypeId, CleanupFn>,
+    // set once an exit is requested
+    exit: Option<AppExit>,
 }
 
 /// The core entity-component world representation.
@@ -36,10 +80,34 @@ impl World {
             components: HashMap::new(),
             resources: HashMap::new(),
             ids: HopSlotMap::with_key(),
-            active: true,
+            hooks: HashMap::new(),
+            required: HashMap::new(),
+            observers: HashMap::new(),
+            removed: HashMap::new(),
+            entity_mappers: HashMap::new(),
+            registry: TypeRegistry::default(),
+            dynamic: DynamicComponents::default(),
+            #[cfg(feature = "serde")]
+            serde: Default::default(),
+            resource_cloners: HashMap::new(),
+            cleanups: Vec::new(),
+            storage_cleanups: HashMap::new(),
+            exit: None,
         };
-        instance.register::<EntityId>();
+        // cloneable so snapshots can restore them, but skipped by `World::clone_entity`
+        instance.register_cloneable::<EntityId>();
+        let mut parent = instance.register_cloneable::<Parent>();
+        parent.map_entities();
+        #[cfg(feature = "serde")]
+        parent.serializable("Parent");
+        let mut children = instance.register_cloneable::<Children>();
+        children.map_entities();
+        #[cfg(feature = "serde")]
+        children.serializable("Children");
+        instance.register_cloneable::<PrefabLink>();
         instance.add_resource(Commands::default());
+        instance.add_cleanup(remove_entity_observers);
+        instance.add_cleanup(remove_dynamic_components);
 
         instance
     }
@@ -51,28 +119,189 @@ impl World {
         EntityBuilder::new(key, self)
     }
 
-    /// Removes an entity with a given ID.
+    /// Removes an entity with a given ID, along with all of its components.
+    ///
+    /// Any children of the entity are removed recursively, and the entity is detached from its parent.
     pub fn remove_entity(&mut self, key: EntityId) {
+        if !self.ids.contains_key(key) {
+            return;
+        }
+
+        self.remove_parent(key);
+        for child in self.descendants(key).collect::<Vec<_>>() {
+            self.despawn(child);
+        }
+        self.despawn(key);
+    }
+
+    /// Removes a single entity and its components without touching the hierarchy.
+    fn despawn(&mut self, key: EntityId) {
+        let on_remove = self
+            .components
+            .iter()
+            .filter(|(_, storage)| storage.contains_entry(key))
+            .flat_map(|(id, storage)| {
+                let hook = self.hooks.get(id).and_then(|hooks| hooks.on_remove);
+
+                hook.into_iter().chain([storage.remove_trigger()])
+            })
+            .collect::<Vec<_>>();
+        for hook in on_remove {
+            hook(self, key);
+        }
+
+        let removed = self
+            .components
+            .iter()
+            .filter(|(id, storage)| self.tracks_removals(**id) && storage.contains_entry(key))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in removed {
+            self.record_removal(id, key);
+        }
+
+        for storage in self.components.values_mut() {
+            storage.remove_entry(key);
+        }
         self.ids.remove(key);
-        /*
-        TODO: how do we remove components when we delete an entity?
-        for storage in self.components.values() {
-            storage.borrow_mut().downcast_mut::<ComponentStorage<_>>().unwrap().remove(key);
+
+        self.run_cleanups(key);
+    }
+
+    /// Adds a function that runs every time an entity is removed, after its components are gone.
+    pub(crate) fn add_cleanup(&mut self, cleanup: CleanupFn) {
+        self.cleanups.push(cleanup);
+    }
+
+    /// Like `World::add_cleanup`, but tied to the component type `T`, so it is copied along with `T` when entities are moved
+    /// to a world that does not have `T` yet.
+    pub(crate) fn add_storage_cleanup<T>(&mut self, cleanup: CleanupFn)
+    where
+        T: Component + 'static,
+    {
+        self.storage_cleanups
+            .insert(TypeId::of::<ComponentStorage<T>>(), cleanup);
+    }
+
+    /// Runs every cleanup for a removed entity.
+    pub(crate) fn run_cleanups(&mut self, key: EntityId) {
+        let cleanups = self
+            .cleanups
+            .iter()
+            .chain(self.storage_cleanups.values())
+            .copied()
+            .collect::<Vec<_>>();
+        for cleanup in cleanups {
+            cleanup(self, key);
         }
-        */
     }
 
-    /// Registers a component type with the world.
-    /// # Warning
-    /// Registering a component twice will delete previously stored components!
-    pub fn register<T>(&mut self)
+    /// Registers a component type with the world, adding it to the `TypeRegistry` under its type name.
+    /// The returned `ComponentRegistration` can be used to configure the component, e.g. to set lifecycle hooks or a stable name.
+    ///
+    /// Registering is optional, since `World::add_component` registers missing types itself. The first registration applies
+    /// `Component::configure`. Registering a type that is already registered keeps its components and configuration,
+    /// so plugins can safely register the components they share.
+    pub fn register<T>(&mut self) -> ComponentRegistration<'_, T>
+    where
+        T: Component + 'static,
+    {
+        if !self.contains_storage::<T>() {
+            let id = TypeId::of::<ComponentStorage<T>>();
+            self.components
+                .insert(id, Box::new(ComponentStorage::<T>::new()));
+            self.registry.info_mut::<T>();
+            T::configure(&mut ComponentRegistration::new(self));
+        }
+
+        ComponentRegistration::new(self)
+    }
+
+    /// Registers a component type whose values are copied by `World::clone_entity`, and cloned through its `ComponentInfo`.
+    /// If `T` is already registered, its storage becomes cloneable and keeps its components and configuration.
+    pub fn register_cloneable<T>(&mut self) -> ComponentRegistration<'_, T>
+    where
+        T: Component + Clone + 'static,
+    {
+        let mut registration = self.register::<T>();
+        registration.cloneable();
+
+        registration
+    }
+
+    /// Gets the lifecycle hooks of a component type.
+    fn hooks<T>(&self) -> ComponentHooks
     where
         T: Component + 'static,
     {
         let id = TypeId::of::<ComponentStorage<T>>();
 
+        self.hooks.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Creates a new entity with a copy of every component of `key` whose type was registered with `World::register_cloneable`.
+    /// Other components are skipped; use `World::uncloneable_components` to find out which.
+    ///
+    /// The copy is added as a child of the original's parent, but the original's children are not copied.
+    /// The copied components add the components they require, run their `on_add` and `on_insert` hooks and fire `OnAdd` observers,
+    /// as if they were added one by one.
+    /// # Panics
+    /// Panics if the entity does not exist.
+    pub fn clone_entity(&mut self, key: EntityId) -> EntityId {
+        assert!(self.ids.contains_key(key), "Entity {key:?} does not exist");
+
+        let id = self.new_entity().build();
+        let managed = managed_storages();
+        let mut cloned = Vec::new();
+        for (storage_id, storage) in self.components.iter_mut() {
+            if !managed.contains(storage_id) {
+                storage.clone_entry(key, id);
+                if storage.contains_entry(id) {
+                    cloned.push(*storage_id);
+                }
+            }
+        }
+        if let Some(parent) = self.parent(key) {
+            self.add_child(parent, id);
+        }
+        for storage_id in cloned {
+            self.component_added(storage_id, id);
+        }
+
+        id
+    }
+
+    /// Adds the required components, runs the `on_add` and `on_insert` hooks and fires `OnAdd` observers for a component
+    /// inserted into `key` through its type-erased storage, like `World::add_component` does for a new component.
+    pub(crate) fn component_added(&mut self, id: TypeId, key: EntityId) {
+        let Some(storage) = self.components.get(&id) else {
+            return;
+        };
+        let trigger = storage.add_trigger();
+        let hooks = self.hooks.get(&id).copied().unwrap_or_default();
+
+        self.add_required(id, key);
+        if let Some(on_add) = hooks.on_add {
+            on_add(self, key);
+        }
+        if let Some(on_insert) = hooks.on_insert {
+            on_insert(self, key);
+        }
+        trigger(self, key);
+    }
+
+    /// Gets the type names of all components of `key` that `World::clone_entity` would skip.
+    /// Components managed by the world itself, like `EntityId`, `Parent` and `Children`, are not reported.
+    pub fn uncloneable_components(&self, key: EntityId) -> Vec<&'static str> {
+        let managed = managed_storages();
+
         self.components
-            .insert(id, Box::new(ComponentStorage::<T>::new()));
+            .iter()
+            .filter(|(id, storage)| {
+                !managed.contains(id) && !storage.is_cloneable() && storage.contains_entry(key)
+            })
+            .map(|(_, storage)| storage.type_name())
+            .collect()
     }
 
     /// Checks if the world has storage for a specific component type.
@@ -85,6 +314,14 @@ impl World {
         self.components.contains_key(&id)
     }
 
+    /// Checks if the storage keyed by `id` has a component for an entity.
+    pub(crate) fn contains_entry(&self, id: TypeId, key: EntityId) -> bool {
+        self.components
+            .get(&id)
+            .is_some_and(|storage| storage.contains_entry(key))
+            && self.ids.contains_key(key)
+    }
+
     /// Gets a reference to the storage for a specific component type if it exists.
     fn storage<T>(&self) -> Option<&ComponentStorage<T>>
     where
@@ -94,11 +331,12 @@ impl World {
 
         self.components
             .get(&id)?
+            .as_any()
             .downcast_ref::<ComponentStorage<T>>()
     }
 
     /// Gets a reference to the storage for a specific component type if it exists.
-    fn storage_mut<T>(&mut self) -> Option<&mut ComponentStorage<T>>
+    pub(crate) fn storage_mut<T>(&mut self) -> Option<&mut ComponentStorage<T>>
     where
         T: Component + 'static,
     {
@@ -106,6 +344,7 @@ impl World {
 
         self.components
             .get_mut(&id)?
+            .as_any_mut()
             .downcast_mut::<ComponentStorage<T>>()
     }
 
@@ -119,25 +358,49 @@ impl World {
     {
         self.storage::<T>()
             .map(|inner| inner.contains(key))
-            .is_some()
+            .unwrap_or(false)
             && self.ids.contains_key(key)
     }
 
-    /// Adds a component of a specific type to an entity.
-    /// # Panics
-    /// Panics if the component `T` has not been registered.
+    /// Adds a component of a specific type to an entity, registering `T` first if needed.
+    /// If the entity did not have a `T`, the components `T` requires are added before its hooks run.
     pub fn add_component<T>(&mut self, key: EntityId, entry: T) -> Option<T>
     where
         T: Component + 'static,
     {
-        assert!(
-            self.contains_storage::<T>(),
-            "Component {} is not registered",
-            std::any::type_name::<T>()
-        );
+        if !self.contains_storage::<T>() {
+            self.register::<T>();
+        }
+        let hooks = self.hooks::<T>();
         let storage = self.storage_mut::<T>().unwrap();
+        let added = !storage.contains(key);
+        let previous = storage.insert(key, entry);
+
+        if added {
+            self.add_required(TypeId::of::<ComponentStorage<T>>(), key);
+        }
+        if let (true, Some(on_add)) = (added, hooks.on_add) {
+            on_add(self, key);
+        }
+        if let Some(on_insert) = hooks.on_insert {
+            on_insert(self, key);
+        }
+        if added {
+            trigger_on_add::<T>(self, key);
+        }
 
-        storage.insert(key, entry)
+        previous
+    }
+
+    /// Adds the components required by the component stored under `id` that `key` is missing, see `ComponentRegistration::requires`.
+    pub(crate) fn add_required(&mut self, id: TypeId, key: EntityId) {
+        let Some(required) = self.required.get(&id).cloned() else {
+            return;
+        };
+
+        for (_, add) in required {
+            add(self, key);
+        }
     }
 
     /// Adds a resource to the world.
@@ -156,6 +419,13 @@ impl World {
     where
         T: Component + 'static,
     {
+        if self.contains_component::<T>(key) {
+            if let Some(on_remove) = self.hooks::<T>().on_remove {
+                on_remove(self, key);
+            }
+            trigger_on_remove::<T>(self, key);
+            self.record_removal(TypeId::of::<ComponentStorage<T>>(), key);
+        }
         let mut storage = self.storage_mut::<T>()?;
 
         storage.remove(key)
@@ -270,13 +540,29 @@ impl World {
         self.get_resource_mut::<Commands>().unwrap()
     }
 
-    /// Checks if the `World` is still active.
+    /// Checks if the `World` is still active, i.e. no exit was requested.
     pub fn active(&self) -> bool {
-        self.active
+        self.exit.is_none()
     }
 
-    /// Shuts down `World`, ending the execution of the app.
+    /// Shuts down `World`, ending the execution of the app successfully. Same as `World::exit(AppExit::Success)`.
     pub fn shutdown(&mut self) {
-        self.active = false;
+        self.exit(AppExit::Success);
+    }
+
+    /// Requests the app to exit with `exit`, which `App::run` returns. The shutdown systems run at the end of the frame,
+    /// and can still veto the exit with `World::cancel_exit`. Later requests replace earlier ones.
+    pub fn exit(&mut self, exit: AppExit) {
+        self.exit = Some(exit);
+    }
+
+    /// Vetoes a requested exit, e.g. from a shutdown system when there are unsaved changes. The app keeps running.
+    pub fn cancel_exit(&mut self) {
+        self.exit = None;
+    }
+
+    /// Gets the requested exit, if there is one.
+    pub fn exit_requested(&self) -> Option<AppExit> {
+        self.exit
     }
 }
@@ -0,0 +1,6 @@
+// Misuse of the derive macros, see the matching `.stderr` files for the expected errors.
+#[test]
+fn compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,11 @@
+use ecs::prelude::*;
+
+#[derive(Component)]
+struct Position(f32, f32);
+
+#[derive(Bundle)]
+enum Shape {
+    Point(Position),
+}
+
+fn main() {}
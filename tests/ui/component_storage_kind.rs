@@ -0,0 +1,7 @@
+use ecs::prelude::*;
+
+#[derive(Component)]
+#[component(storage = "packed")]
+struct Position(f32, f32);
+
+fn main() {}
@@ -0,0 +1,7 @@
+use ecs::prelude::*;
+
+#[derive(Component)]
+#[component(sparse)]
+struct Position(f32, f32);
+
+fn main() {}
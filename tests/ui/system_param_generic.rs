@@ -0,0 +1,9 @@
+use ecs::prelude::*;
+use ecs::query::Query;
+
+#[derive(SystemParam)]
+struct Params<'w, Q: Query + 'static> {
+    query: QueryParam<'w, Q>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use ecs::prelude::*;
+use std::cell::Ref;
+
+#[derive(Component)]
+struct Gravity(f32);
+
+#[derive(SystemParam)]
+struct Params<'w, 's> {
+    gravity: Ref<'w, Gravity>,
+    other: Ref<'s, Gravity>,
+}
+
+fn main() {}